@@ -1,15 +1,15 @@
-#![feature(external_doc, specialization, drain_filter, test, decl_macro)]
+#![feature(specialization, test, decl_macro)]
 //! Reactive extensions library for Rust: a library for
 //! [Reactive Programming](http://reactivex.io/) using
 //! [Observable](crate::observable::Observable), to make
 //! it easier to compose asynchronous or callback-based code.
-#![doc(include = "../README.md")]
 #[macro_use]
 extern crate lazy_static;
 
 #[cfg(test)]
 extern crate float_cmp;
 
+pub mod marble;
 pub mod observable;
 pub mod observer;
 pub mod ops;
@@ -27,10 +27,15 @@ pub mod prelude {
   pub use crate::scheduler::*;
   pub use crate::shared;
   pub use crate::subject;
-  pub use crate::subject::{LocalSubject, SharedSubject, Subject};
+  pub use crate::subject::{
+    AsyncSubject, BehaviorSubject, LocalAsyncSubject, LocalBehaviorSubject,
+    LocalReplaySubject, LocalSubject, ReplaySubject, SharedAsyncSubject,
+    SharedBehaviorSubject, SharedReplaySubject, SharedSubject, Subject,
+  };
   pub use crate::subscriber::Subscriber;
   pub use crate::subscription;
   pub use crate::subscription::*;
-  pub use observer::{Observer, PayloadCopy};
+  pub use crate::marble::Notification;
+  pub use observer::{Observer, PayloadCopy, SinkObserver};
   pub use shared::*;
 }
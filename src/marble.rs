@@ -0,0 +1,406 @@
+//! Marble diagram testing, driven by the virtual clock of a
+//! [`TestScheduler`](crate::scheduler::TestScheduler).
+//!
+//! Every character of a marble string is a frame of one virtual millisecond:
+//!
+//! - `-` a frame passes without any emission.
+//! - `|` the observable completes.
+//! - `#` the observable emits an error.
+//! - `^` the subscription point of a hot observable, its frame zero.
+//! - `(ab)` a group, all the notifications in it are emitted at the frame of
+//!   the `(`. The group still occupies a frame per character.
+//! - spaces are ignored and don't take any frame.
+//! - any other character is emitted as an item.
+//!
+//! By default the items are the characters themselves, and the error is
+//! `"error"`. The `_with` methods map the characters to items and give the
+//! error to emit.
+//!
+//! # Example
+//!
+//! ```
+//! use rxrust::marble::MarbleTest;
+//! use rxrust::prelude::*;
+//!
+//! let test = MarbleTest::new();
+//! let source = test.cold("-a-b-(cd)-|");
+//! test.expect_observable(
+//!   source.map(|c: char| c.to_ascii_uppercase()),
+//!   "-A-B-(CD)-|",
+//! );
+//! ```
+use crate::prelude::*;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The virtual time of a frame.
+pub const FRAME: Duration = Duration::from_millis(1);
+
+/// The count of frames the virtual clock runs for each expectation.
+pub const MAX_FRAMES: u32 = 1000;
+
+/// The error emitted by `#` when no error is given.
+pub const DEFAULT_ERROR: &str = "error";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification<Item, Err> {
+  Next(Item),
+  Error(Err),
+  Complete,
+}
+
+impl<Item, Err> Notification<Item, Err> {
+  pub fn notify<O: Observer<Item, Err>>(self, observer: &mut O) {
+    match self {
+      Notification::Next(v) => observer.next(v),
+      Notification::Error(err) => observer.error(err),
+      Notification::Complete => observer.complete(),
+    }
+  }
+}
+
+/// Notifications with the frame they are emitted at.
+pub type Timeline<Item, Err> = Vec<(usize, Notification<Item, Err>)>;
+
+/// Parses `marbles` into a timeline, the frames are relative to `^` if any.
+/// Notifications before `^` are dropped.
+///
+/// # Panics
+///
+/// Panics if an item character has no value in `values`, or if a group is
+/// not closed.
+pub fn parse_marbles<Item, Err>(
+  marbles: &str,
+  values: &[(char, Item)],
+  error: Err,
+) -> Timeline<Item, Err>
+where
+  Item: Clone,
+  Err: Clone,
+{
+  parse(
+    marbles,
+    |c| {
+      values
+        .iter()
+        .find(|(k, _)| *k == c)
+        .map(|(_, v)| v.clone())
+        .unwrap_or_else(|| panic!("no value given for marble `{}`", c))
+    },
+    error,
+  )
+}
+
+fn parse<Item, Err: Clone>(
+  marbles: &str,
+  value: impl Fn(char) -> Item,
+  error: Err,
+) -> Timeline<Item, Err> {
+  let mut timeline = vec![];
+  let mut frame = 0;
+  let mut zero = 0;
+  let mut group: Option<usize> = None;
+  for c in marbles.chars() {
+    let at = group.unwrap_or(frame);
+    match c {
+      ' ' => continue,
+      '-' => {}
+      '(' => group = Some(frame),
+      ')' => group = None,
+      '^' => zero = frame,
+      '|' => timeline.push((at, Notification::Complete)),
+      '#' => timeline.push((at, Notification::Error(error.clone()))),
+      c => timeline.push((at, Notification::Next(value(c)))),
+    }
+    frame += 1;
+  }
+  assert!(group.is_none(), "unclosed group in marbles `{}`", marbles);
+  timeline
+    .into_iter()
+    .filter(|(at, _)| *at >= zero)
+    .map(|(at, n)| (at - zero, n))
+    .collect()
+}
+
+/// An observable replaying its timeline to every subscriber, relative to the
+/// time it subscribes.
+#[derive(Clone)]
+pub struct ColdObservable<Item, Err> {
+  timeline: Timeline<Item, Err>,
+  scheduler: TestScheduler,
+}
+
+impl<Item, Err> Observable for ColdObservable<Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<Item, Err> SharedObservable for ColdObservable<Item, Err>
+where
+  Item: Send + Sync + 'static,
+  Err: Send + Sync + 'static,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription.clone();
+    let subscriber = Arc::new(Mutex::new(subscriber));
+    for (frame, notification) in self.timeline {
+      let handle = self.scheduler.schedule(
+        |_, (notification, subscriber): (Notification<_, _>, Arc<Mutex<_>>)| {
+          notification.notify(&mut *subscriber.lock().unwrap())
+        },
+        Some(FRAME * frame as u32),
+        (notification, subscriber.clone()),
+      );
+      subscription.add(handle);
+    }
+    subscription
+  }
+}
+
+/// Creates test observables from marbles and checks observables against
+/// marbles, all of them sharing the same virtual clock.
+#[derive(Clone, Default)]
+pub struct MarbleTest {
+  scheduler: TestScheduler,
+}
+
+impl MarbleTest {
+  #[inline]
+  pub fn new() -> Self { Self::default() }
+
+  /// The scheduler driving the virtual clock, give it to the time-based
+  /// operators under test.
+  #[inline]
+  pub fn scheduler(&self) -> TestScheduler { self.scheduler.clone() }
+
+  /// Creates a cold observable emitting the characters of `marbles`.
+  pub fn cold(&self, marbles: &str) -> ColdObservable<char, &'static str> {
+    ColdObservable {
+      timeline: parse(marbles, |c| c, DEFAULT_ERROR),
+      scheduler: self.scheduler(),
+    }
+  }
+
+  /// Creates a cold observable emitting the values mapped to the characters
+  /// of `marbles`, and `error` for `#`.
+  pub fn cold_with<Item, Err>(
+    &self,
+    marbles: &str,
+    values: &[(char, Item)],
+    error: Err,
+  ) -> ColdObservable<Item, Err>
+  where
+    Item: Clone,
+    Err: Clone,
+  {
+    ColdObservable {
+      timeline: parse_marbles(marbles, values, error),
+      scheduler: self.scheduler(),
+    }
+  }
+
+  /// Creates a hot observable emitting the characters of `marbles`, frame
+  /// zero is the current virtual time.
+  pub fn hot(&self, marbles: &str) -> SharedSubject<char, &'static str> {
+    self.hot_timeline(parse(marbles, |c| c, DEFAULT_ERROR))
+  }
+
+  /// Creates a hot observable emitting the values mapped to the characters of
+  /// `marbles`, and `error` for `#`.
+  pub fn hot_with<Item, Err>(
+    &self,
+    marbles: &str,
+    values: &[(char, Item)],
+    error: Err,
+  ) -> SharedSubject<Item, Err>
+  where
+    Item: PayloadCopy + Send + Sync + 'static,
+    Err: PayloadCopy + Send + Sync + 'static,
+  {
+    self.hot_timeline(parse_marbles(marbles, values, error))
+  }
+
+  fn hot_timeline<Item, Err>(
+    &self,
+    timeline: Timeline<Item, Err>,
+  ) -> SharedSubject<Item, Err>
+  where
+    Item: PayloadCopy + Send + Sync + 'static,
+    Err: PayloadCopy + Send + Sync + 'static,
+  {
+    let subject = SharedSubject::new();
+    for (frame, notification) in timeline {
+      self.scheduler.schedule(
+        |_, (notification, mut subject): (_, SharedSubject<Item, Err>)| {
+          notification.notify(&mut subject)
+        },
+        Some(FRAME * frame as u32),
+        (notification, subject.clone()),
+      );
+    }
+    subject
+  }
+
+  /// Subscribes `observable`, runs the virtual clock for [`MAX_FRAMES`]
+  /// frames, and returns what it emitted, relative to the time it was
+  /// subscribed.
+  pub fn record<S>(&self, observable: S) -> Timeline<S::Item, S::Err>
+  where
+    S: SharedObservable,
+    S::Item: Send + Sync + 'static,
+    S::Err: Send + Sync + 'static,
+  {
+    let timeline = Arc::new(Mutex::new(vec![]));
+    let mut subscription = observable.actual_subscribe(Subscriber::shared(
+      RecordObserver {
+        timeline: timeline.clone(),
+        scheduler: self.scheduler(),
+        start: self.scheduler.elapsed(),
+      },
+    ));
+    self.scheduler.advance_by(FRAME * MAX_FRAMES);
+    subscription.unsubscribe();
+    let mut timeline = timeline.lock().unwrap();
+    std::mem::take(&mut *timeline)
+  }
+
+  /// Asserts `observable` emits the characters of `expected`.
+  pub fn expect_observable<S>(&self, observable: S, expected: &str)
+  where
+    S: SharedObservable<Item = char, Err = &'static str>,
+  {
+    let expected = parse(expected, |c| c, DEFAULT_ERROR);
+    self.assert_timeline(observable, expected);
+  }
+
+  /// Asserts `observable` emits the values mapped to the characters of
+  /// `expected`, and `error` for `#`.
+  pub fn expect_observable_with<S>(
+    &self,
+    observable: S,
+    expected: &str,
+    values: &[(char, S::Item)],
+    error: S::Err,
+  ) where
+    S: SharedObservable,
+    S::Item: Clone + PartialEq + Debug + Send + Sync + 'static,
+    S::Err: Clone + PartialEq + Debug + Send + Sync + 'static,
+  {
+    let expected = parse_marbles(expected, values, error);
+    self.assert_timeline(observable, expected);
+  }
+
+  fn assert_timeline<S>(
+    &self,
+    observable: S,
+    expected: Timeline<S::Item, S::Err>,
+  ) where
+    S: SharedObservable,
+    S::Item: PartialEq + Debug + Send + Sync + 'static,
+    S::Err: PartialEq + Debug + Send + Sync + 'static,
+  {
+    let actual = self.record(observable);
+    assert_eq!(
+      actual, expected,
+      "observable emitted (left) differs from the marbles (right)"
+    );
+  }
+}
+
+struct RecordObserver<Item, Err> {
+  timeline: Arc<Mutex<Timeline<Item, Err>>>,
+  scheduler: TestScheduler,
+  start: Duration,
+}
+
+impl<Item, Err> RecordObserver<Item, Err> {
+  fn record(&mut self, notification: Notification<Item, Err>) {
+    let elapsed = self.scheduler.elapsed() - self.start;
+    let frame = (elapsed.as_nanos() / FRAME.as_nanos()) as usize;
+    self.timeline.lock().unwrap().push((frame, notification));
+  }
+}
+
+impl<Item, Err> Observer<Item, Err> for RecordObserver<Item, Err> {
+  fn next(&mut self, value: Item) { self.record(Notification::Next(value)) }
+
+  fn error(&mut self, err: Err) { self.record(Notification::Error(err)) }
+
+  fn complete(&mut self) { self.record(Notification::Complete) }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parse_timeline() {
+    let values = [('a', 1), ('b', 2), ('c', 3)];
+    let timeline = parse_marbles("-a-(bc)-| #", &values, ());
+    assert_eq!(
+      timeline,
+      vec![
+        (1, Notification::Next(1)),
+        (3, Notification::Next(2)),
+        (3, Notification::Next(3)),
+        (8, Notification::Complete),
+        (9, Notification::Error(())),
+      ]
+    );
+
+    let timeline = parse_marbles("a-^-b", &[('a', 1), ('b', 2)], ());
+    assert_eq!(timeline, vec![(2, Notification::Next(2))]);
+  }
+
+  #[test]
+  fn cold() {
+    let test = MarbleTest::new();
+    let source = test.cold("-a-b-(cd)-|");
+    test.expect_observable(source.clone(), "-a-b-(cd)-|");
+    test.expect_observable(source.take(2), "-a-(b|)");
+    test.expect_observable(test.cold("--#"), "--#");
+  }
+
+  #[test]
+  fn cold_with() {
+    let test = MarbleTest::new();
+    let source = test.cold_with("-a-b-|", &[('a', 1), ('b', 2)], "");
+    test.expect_observable_with(
+      source.map(|v| v * 10),
+      "-x-y-|",
+      &[('x', 10), ('y', 20)],
+      "",
+    );
+  }
+
+  #[test]
+  fn hot() {
+    let test = MarbleTest::new();
+    let source = test.hot("-a-^-b-c-|");
+    test.expect_observable(source, "--b-c-|");
+  }
+
+  #[test]
+  fn time_based_operator() {
+    let test = MarbleTest::new();
+    let source = test.cold("a-b-c|");
+    test.expect_observable(
+      source.delay_on(Duration::from_millis(3), test.scheduler()),
+      "---a-b-c|",
+    );
+  }
+
+  #[test]
+  #[should_panic]
+  fn mismatch() {
+    let test = MarbleTest::new();
+    test.expect_observable(test.cold("-a|"), "a-|");
+  }
+}
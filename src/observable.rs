@@ -10,8 +10,11 @@ pub use of::{of, of_fn, of_option, of_result, of_sequence};
 pub(crate) mod from_future;
 pub use from_future::{from_future, from_future_result};
 
+pub(crate) mod from_stream;
+pub use from_stream::{from_stream, from_stream_on};
+
 pub(crate) mod interval;
-pub use interval::{interval, interval_at};
+pub use interval::{interval, interval_at, interval_at_on, interval_on};
 
 pub(crate) mod connectable_observable;
 pub use connectable_observable::{
@@ -34,28 +37,44 @@ pub use observable_next::*;
 mod observable_comp;
 use crate::prelude::*;
 pub use observable_comp::*;
+mod to_stream;
+pub use to_stream::*;
+mod to_future;
+pub use to_future::*;
 
+pub use crate::ops::combine_latest::combine_latest_all;
 use crate::ops::default_if_empty::DefaultIfEmptyOp;
 use ops::{
   box_it::{BoxOp, IntoBox},
+  catch_error::CatchErrorOp,
+  combine_latest::CombineLatestOp,
+  concat::ConcatOp,
   delay::DelayOp,
+  exhaust_map::ExhaustMapOp,
   filter::FilterOp,
   first::FirstOrOp,
+  flat_map::FlatMapOp,
   last::LastOrOp,
   map::MapOp,
+  map_err::MapErrOp,
   merge::MergeOp,
   observe_on::ObserveOnOp,
   ref_count::{RefCount, RefCountCreator},
+  retry::{RetryOp, RetryWhenOp},
+  retry_with_backoff::{BackoffPolicy, RetryWithBackoffOp},
   sample::SampleOp,
   scan::ScanOp,
   skip::SkipOp,
   skip_last::SkipLastOp,
   subscribe_on::SubscribeOnOP,
+  switch_map::SwitchMapOp,
   take::TakeOp,
   take_last::TakeLastOp,
   take_until::TakeUntilOp,
   take_while::TakeWhileOp,
   throttle_time::{ThrottleEdge, ThrottleTimeOp},
+  timeout::{TimeoutOp, TimeoutWithOp},
+  with_latest_from::WithLatestFromOp,
   zip::ZipOp,
   Accum, AverageOp, CountOp, MinMaxOp, ReduceOp, SumOp,
 };
@@ -161,6 +180,243 @@ pub trait Observable {
     }
   }
 
+  /// Creates a new stream which calls a closure on the error of the source,
+  /// and emits its return as the error.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// let mut errors = vec![];
+  /// observable::throw(404)
+  ///   .map_err(|code| format!("status {}", code))
+  ///   .subscribe_err(|_| {}, |e| errors.push(e));
+  ///
+  /// assert_eq!(errors, vec!["status 404".to_string()]);
+  /// ```
+  #[inline]
+  fn map_err<E, F>(self, f: F) -> MapErrOp<Self, F>
+  where
+    Self: Sized,
+    F: FnOnce(Self::Err) -> E,
+  {
+    MapErrOp {
+      source: self,
+      func: f,
+    }
+  }
+
+  /// Converts the error of the source into `E` with [`From`], so sources of
+  /// different error types can be combined by operators like
+  /// [`merge`](Observable::merge) or [`zip`](Observable::zip).
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  /// use std::num::ParseIntError;
+  ///
+  /// #[derive(Debug, PartialEq)]
+  /// enum Error {
+  ///   Unit,
+  ///   Parse(ParseIntError),
+  /// }
+  /// impl From<()> for Error {
+  ///   fn from(_: ()) -> Self { Error::Unit }
+  /// }
+  /// impl From<ParseIntError> for Error {
+  ///   fn from(e: ParseIntError) -> Self { Error::Parse(e) }
+  /// }
+  ///
+  /// let parse_error = "x".parse::<i32>().unwrap_err();
+  /// let mut errors = vec![];
+  /// observable::of_result::<i32, _>(Err(parse_error.clone()))
+  ///   .err_into::<Error>()
+  ///   .merge(observable::empty().err_into())
+  ///   .subscribe_err(|_| {}, |e| errors.push(e));
+  ///
+  /// assert_eq!(errors, vec![Error::Parse(parse_error)]);
+  /// ```
+  #[inline]
+  fn err_into<E>(self) -> MapErrOp<Self, fn(Self::Err) -> E>
+  where
+    Self: Sized,
+    E: From<Self::Err>,
+  {
+    self.map_err(E::from)
+  }
+
+  /// Maps each item emitted by the source observable to an inner observable,
+  /// and merges the emissions of all the inner observables into one.
+  ///
+  /// Completes when the source and all the inner observables complete. Emits
+  /// error when the source or any inner observable emits it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut values = vec![];
+  /// observable::from_iter(0..3)
+  ///   .flat_map(|v| observable::from_iter(vec![v; 2]))
+  ///   .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![0, 0, 1, 1, 2, 2]);
+  /// ```
+  #[inline]
+  fn flat_map<Inner, F>(self, f: F) -> FlatMapOp<Self, F>
+  where
+    Self: Sized,
+    F: FnMut(Self::Item) -> Inner,
+    Inner: Observable<Err = Self::Err>,
+  {
+    self.flat_map_concurrent(f, usize::MAX)
+  }
+
+  /// Works like [`flat_map`](Observable::flat_map), but subscribes at most
+  /// `concurrent` inner observables at the same time. Items emitted by the
+  /// source while the limit is reached are queued, and mapped to their inner
+  /// observable once an active one completes.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `concurrent` is 0.
+  #[inline]
+  fn flat_map_concurrent<Inner, F>(
+    self,
+    f: F,
+    concurrent: usize,
+  ) -> FlatMapOp<Self, F>
+  where
+    Self: Sized,
+    F: FnMut(Self::Item) -> Inner,
+    Inner: Observable<Err = Self::Err>,
+  {
+    assert!(concurrent > 0, "flat_map can't subscribe 0 inner observables.");
+    FlatMapOp {
+      source: self,
+      func: f,
+      concurrent,
+    }
+  }
+
+  /// Maps each item emitted by the source observable to an inner observable,
+  /// and subscribes the inner observables one at a time, in order. Items
+  /// emitted by the source while an inner observable is active are queued.
+  ///
+  /// This is an alias for `flat_map_concurrent(f, 1)`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut values = vec![];
+  /// observable::from_iter(0..3)
+  ///   .concat_map(|v| observable::from_iter(vec![v; 2]))
+  ///   .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![0, 0, 1, 1, 2, 2]);
+  /// ```
+  #[inline]
+  fn concat_map<Inner, F>(self, f: F) -> FlatMapOp<Self, F>
+  where
+    Self: Sized,
+    F: FnMut(Self::Item) -> Inner,
+    Inner: Observable<Err = Self::Err>,
+  {
+    self.flat_map_concurrent(f, 1)
+  }
+
+  /// Maps each item emitted by the source observable to an inner observable,
+  /// and only mirrors the most recently created one. When the source emits a
+  /// new item, the previous inner observable is unsubscribed.
+  ///
+  /// Completes when both the source and the active inner observable
+  /// complete. Emits error when the source or the active inner observable
+  /// emits it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut search = Subject::new();
+  /// let mut request_a = Subject::new();
+  /// let mut request_b = Subject::new();
+  /// let requests = [request_a.clone(), request_b.clone()];
+  ///
+  /// search
+  ///   .clone()
+  ///   .switch_map(move |i: usize| requests[i].clone())
+  ///   .subscribe(|v| println!("{}", v));
+  ///
+  /// search.next(0);
+  /// search.next(1);
+  /// // `request_a` was unsubscribed when `search` emitted again.
+  /// request_a.next("a");
+  /// request_b.next("b");
+  ///
+  /// // print log:
+  /// // b
+  /// ```
+  #[inline]
+  fn switch_map<Inner, F>(self, f: F) -> SwitchMapOp<Self, F>
+  where
+    Self: Sized,
+    F: FnMut(Self::Item) -> Inner,
+    Inner: Observable<Err = Self::Err>,
+  {
+    SwitchMapOp {
+      source: self,
+      func: f,
+    }
+  }
+
+  /// Maps an item emitted by the source observable to an inner observable,
+  /// and ignores every source item emitted while that inner observable is
+  /// still active. Once it completes, the next source item is mapped again.
+  ///
+  /// Completes when both the source and the active inner observable
+  /// complete. Emits error when the source or the active inner observable
+  /// emits it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut clicks = Subject::new();
+  /// let mut request = Subject::new();
+  /// let c_request = request.clone();
+  ///
+  /// clicks
+  ///   .clone()
+  ///   .exhaust_map(move |_: ()| c_request.clone())
+  ///   .subscribe(|v| println!("{}", v));
+  ///
+  /// clicks.next(());
+  /// // ignored, the first request is not completed yet.
+  /// clicks.next(());
+  /// request.next("submitted");
+  ///
+  /// // print log:
+  /// // submitted
+  /// ```
+  #[inline]
+  fn exhaust_map<Inner, F>(self, f: F) -> ExhaustMapOp<Self, F>
+  where
+    Self: Sized,
+    F: FnMut(Self::Item) -> Inner,
+    Inner: Observable<Err = Self::Err>,
+  {
+    ExhaustMapOp {
+      source: self,
+      func: f,
+    }
+  }
+
   /// combine two Observables into one by merging their emissions
   ///
   /// # Example
@@ -191,6 +447,201 @@ pub trait Observable {
     }
   }
 
+  /// Emits all items of the source observable, and then all items of `o`
+  /// once the source completes. Never interleaves the emissions of the two
+  /// observables, and `o` is not subscribed if the source emits an error.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// let mut values = vec![];
+  /// observable::from_iter(0..3)
+  ///   .concat(observable::from_iter(3..6))
+  ///   .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![0, 1, 2, 3, 4, 5]);
+  /// ```
+  #[inline]
+  fn concat<S>(self, o: S) -> ConcatOp<Self, S>
+  where
+    Self: Sized,
+    S: Observable<Item = Self::Item, Err = Self::Err>,
+  {
+    ConcatOp {
+      source1: self,
+      source2: o,
+    }
+  }
+
+  /// Mirrors the source observable, but when it emits an error, unsubscribes
+  /// it and continues with the observable returned by `handler` for that
+  /// error, which may have another error type.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// let mut values = vec![];
+  /// observable::create(|mut subscriber| {
+  ///   subscriber.next(0);
+  ///   subscriber.next(1);
+  ///   subscriber.error("network error");
+  /// })
+  /// .catch_error(|_| observable::of(-1))
+  ///   .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![0, 1, -1]);
+  /// ```
+  #[inline]
+  fn catch_error<Inner, F>(self, handler: F) -> CatchErrorOp<Self, F>
+  where
+    Self: Sized,
+    F: FnOnce(Self::Err) -> Inner,
+    Inner: Observable<Item = Self::Item>,
+  {
+    CatchErrorOp {
+      source: self,
+      func: handler,
+    }
+  }
+
+  /// Mirrors the source observable, but resubscribes to it when it emits an
+  /// error, at most `count` times. The error is only emitted once all the
+  /// retries failed.
+  ///
+  /// The source is resubscribed from within its error notification, so a
+  /// source failing synchronously while it's being subscribed nests one
+  /// subscription per retry on the stack. Keep `count` small for such a
+  /// source, or use [`retry_when`](Observable::retry_when), which resubscribes
+  /// it in a loop.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// # use std::cell::Cell;
+  /// let attempts = Cell::new(0);
+  /// let mut values = vec![];
+  /// observable::create(|mut subscriber| {
+  ///   attempts.set(attempts.get() + 1);
+  ///   if attempts.get() < 3 {
+  ///     subscriber.error(());
+  ///   } else {
+  ///     subscriber.next(attempts.get());
+  ///     subscriber.complete();
+  ///   }
+  /// })
+  /// .retry(5)
+  /// .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![3]);
+  /// ```
+  #[inline]
+  fn retry(self, count: usize) -> RetryOp<Self>
+  where
+    Self: Sized,
+  {
+    RetryOp {
+      source: self,
+      count,
+    }
+  }
+
+  /// Mirrors the source observable, and resubscribes to it each time the
+  /// notifier returned by `notifier` emits. `notifier` is given a subject
+  /// emitting the errors of the source, the result completes or emits an
+  /// error when the notifier does.
+  ///
+  /// The subject is a [`LocalSubject`] when the result is subscribed as a
+  /// local observable, and a [`SharedSubject`] once it's converted with
+  /// `to_shared`, so a single `notifier` serves one of them only.
+  ///
+  /// # Example
+  ///
+  /// Resubscribes twice, then completes when the notifier completes.
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// # use std::cell::Cell;
+  /// let subscribed = Cell::new(0);
+  /// let mut values = vec![];
+  /// observable::create(|mut subscriber| {
+  ///   subscribed.set(subscribed.get() + 1);
+  ///   subscriber.next(subscribed.get());
+  ///   subscriber.error(());
+  /// })
+  /// .retry_when(|errors| errors.take(2))
+  /// .subscribe(|v| values.push(v));
+  ///
+  /// assert_eq!(values, vec![1, 2, 3]);
+  /// ```
+  #[inline]
+  fn retry_when<F, N, P, U>(self, notifier: F) -> RetryWhenOp<Self, F>
+  where
+    Self: Sized,
+    F: FnOnce(Subject<P, U>) -> N,
+  {
+    RetryWhenOp {
+      source: self,
+      func: notifier,
+    }
+  }
+
+  /// Same as [`retry`](Observable::retry), but waits before each
+  /// resubscription, longer and longer as described by `policy`. The delays
+  /// are scheduled on the thread pool.
+  #[inline]
+  fn retry_with_backoff(
+    self,
+    policy: BackoffPolicy,
+  ) -> RetryWithBackoffOp<Self>
+  where
+    Self: Sized,
+  {
+    self.retry_with_backoff_on(policy, Schedulers::ThreadPool)
+  }
+
+  /// Same as [`retry_with_backoff`](Observable::retry_with_backoff), but the
+  /// delays are scheduled on `scheduler`, a [`Scheduler`] or a
+  /// [`LocalScheduler`].
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// # use rxrust::ops::retry_with_backoff::BackoffPolicy;
+  /// # use std::time::Duration;
+  /// let scheduler = TestScheduler::new();
+  /// let policy = BackoffPolicy {
+  ///   initial_delay: Duration::from_millis(10),
+  ///   max_attempts: 2,
+  ///   ..Default::default()
+  /// };
+  /// observable::throw("network error")
+  ///   .retry_with_backoff_on(policy, scheduler.clone())
+  ///   .to_shared()
+  ///   .subscribe_err(|_: ()| {}, |e| println!("{}", e));
+  ///
+  /// // retries after 10ms, then 20ms, and emits the error.
+  /// scheduler.advance_by(Duration::from_millis(30));
+  /// ```
+  #[inline]
+  fn retry_with_backoff_on<SD>(
+    self,
+    policy: BackoffPolicy,
+    scheduler: SD,
+  ) -> RetryWithBackoffOp<Self, SD>
+  where
+    Self: Sized,
+  {
+    RetryWithBackoffOp {
+      source: self,
+      policy,
+      scheduler,
+    }
+  }
+
   /// Emit only those items from an Observable that pass a predicate test
   /// # Example
   ///
@@ -845,20 +1296,28 @@ pub trait Observable {
   where
     Self: Sized,
   {
-    DelayOp {
-      source: self,
-      delay: dur,
-    }
+    self.delay_on(dur, Schedulers::ThreadPool)
   }
 
   #[inline]
   fn delay_at(self, at: Instant) -> DelayOp<Self>
+  where
+    Self: Sized,
+  {
+    self.delay(at.elapsed())
+  }
+
+  /// Same as [`delay`](Observable::delay), but the delayed subscription is
+  /// scheduled on `scheduler`, a [`Scheduler`] or a [`LocalScheduler`].
+  #[inline]
+  fn delay_on<SD>(self, dur: Duration, scheduler: SD) -> DelayOp<Self, SD>
   where
     Self: Sized,
   {
     DelayOp {
       source: self,
-      delay: at.elapsed(),
+      delay: dur,
+      scheduler,
     }
   }
 
@@ -953,6 +1412,22 @@ pub trait Observable {
     duration: Duration,
     edge: ThrottleEdge,
   ) -> ThrottleTimeOp<Self>
+  where
+    Self: Sized,
+  {
+    self.throttle_time_on(duration, edge, Schedulers::ThreadPool)
+  }
+
+  /// Same as [`throttle_time`](Observable::throttle_time), but the throttle
+  /// windows are scheduled on `scheduler`, a [`Scheduler`] or a
+  /// [`LocalScheduler`].
+  #[inline]
+  fn throttle_time_on<SD>(
+    self,
+    duration: Duration,
+    edge: ThrottleEdge,
+    scheduler: SD,
+  ) -> ThrottleTimeOp<Self, SD>
   where
     Self: Sized,
   {
@@ -960,6 +1435,79 @@ pub trait Observable {
       source: self,
       duration,
       edge,
+      scheduler,
+    }
+  }
+
+  /// Mirrors the source Observable, but emits a
+  /// [`TimeoutError::Elapsed`](crate::ops::timeout::TimeoutError::Elapsed)
+  /// error if no item arrives within `dur` of the subscription or of the
+  /// previous item. The errors of the source are wrapped in
+  /// [`TimeoutError::Source`](crate::ops::timeout::TimeoutError::Source).
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// # use rxrust::prelude::*;
+  /// # use rxrust::ops::timeout::TimeoutError;
+  /// # use std::time::Duration;
+  /// let scheduler = TestScheduler::new();
+  /// observable::never()
+  ///   .timeout_on(Duration::from_millis(10), scheduler.clone())
+  ///   .to_shared()
+  ///   .subscribe_err(|_| {}, |e| assert_eq!(e, TimeoutError::Elapsed));
+  ///
+  /// scheduler.advance_by(Duration::from_millis(10));
+  /// ```
+  #[inline]
+  fn timeout(self, dur: Duration) -> TimeoutOp<Self>
+  where
+    Self: Sized,
+  {
+    self.timeout_on(dur, Schedulers::ThreadPool)
+  }
+
+  /// Same as [`timeout`](Observable::timeout), but the timers are scheduled
+  /// on `scheduler`, a [`Scheduler`] or a [`LocalScheduler`].
+  #[inline]
+  fn timeout_on<SD>(self, dur: Duration, scheduler: SD) -> TimeoutOp<Self, SD>
+  where
+    Self: Sized,
+  {
+    TimeoutOp {
+      source: self,
+      dur,
+      scheduler,
+    }
+  }
+
+  /// Mirrors the source Observable, but switches to `fallback` if no item
+  /// arrives within `dur` of the subscription or of the previous item.
+  #[inline]
+  fn timeout_with<F>(self, dur: Duration, fallback: F) -> TimeoutWithOp<Self, F>
+  where
+    Self: Sized,
+  {
+    self.timeout_with_on(dur, fallback, Schedulers::ThreadPool)
+  }
+
+  /// Same as [`timeout_with`](Observable::timeout_with), but the timers are
+  /// scheduled on `scheduler`, a [`Scheduler`] or a [`LocalScheduler`].
+  #[inline]
+  fn timeout_with_on<F, SD>(
+    self,
+    dur: Duration,
+    fallback: F,
+    scheduler: SD,
+  ) -> TimeoutWithOp<Self, F, SD>
+  where
+    Self: Sized,
+  {
+    TimeoutWithOp {
+      source: self,
+      dur,
+      fallback,
+      scheduler,
     }
   }
 
@@ -980,6 +1528,91 @@ pub trait Observable {
     ZipOp { a: self, b: other }
   }
 
+  /// Combines the latest items of two observables with `binary_op`. Emits
+  /// whenever either observable emits, once both of them have emitted at
+  /// least once.
+  ///
+  /// Completes when both observables complete. Emits error when either
+  /// observable emits it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut a = Subject::new();
+  /// let mut b = Subject::new();
+  /// a.clone()
+  ///   .combine_latest(b.clone(), |a: i32, b: i32| a + b)
+  ///   .subscribe(|v| println!("{}", v));
+  ///
+  /// a.next(1);
+  /// b.next(10);
+  /// a.next(2);
+  ///
+  /// // print log:
+  /// // 11
+  /// // 12
+  /// ```
+  #[inline]
+  fn combine_latest<U, F, Out>(
+    self,
+    other: U,
+    binary_op: F,
+  ) -> CombineLatestOp<Self, U, F>
+  where
+    Self: Sized,
+    U: Observable<Err = Self::Err>,
+    F: FnMut(Self::Item, U::Item) -> Out,
+  {
+    CombineLatestOp {
+      a: self,
+      b: other,
+      binary_op,
+    }
+  }
+
+  /// Pairs every item emitted by the source observable with the latest item
+  /// emitted by `other`. Only the source drives the emissions, and source
+  /// items are dropped until `other` has emitted at least once.
+  ///
+  /// Completes when the source completes. Emits error when either
+  /// observable emits it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use rxrust::prelude::*;
+  ///
+  /// let mut requests = Subject::new();
+  /// let mut config = Subject::new();
+  /// requests
+  ///   .clone()
+  ///   .with_latest_from(config.clone())
+  ///   .subscribe(|v| println!("{:?}", v));
+  ///
+  /// requests.next(1);
+  /// config.next("v1");
+  /// requests.next(2);
+  /// config.next("v2");
+  /// requests.next(3);
+  ///
+  /// // print log:
+  /// // (2, "v1")
+  /// // (3, "v2")
+  /// ```
+  #[inline]
+  fn with_latest_from<U>(self, other: U) -> WithLatestFromOp<Self, U>
+  where
+    Self: Sized,
+    U: Observable<Err = Self::Err>,
+  {
+    WithLatestFromOp {
+      source: self,
+      other,
+    }
+  }
+
   /// Emits default value if Observable completed with empty result
   ///
   /// #Example
@@ -1,15 +1,8 @@
 use crate::prelude::*;
-use futures::{
-  executor::ThreadPool, future::Future, future::FutureExt, task::SpawnExt,
-};
+use crate::scheduler::spawn_default;
+use futures::{future::Future, future::FutureExt};
 use observable::of;
 use std::marker::PhantomData;
-use std::sync::Mutex;
-
-lazy_static! {
-  pub static ref DEFAULT_RUNTIME: Mutex<ThreadPool> =
-    Mutex::new(ThreadPool::new().unwrap());
-}
 
 /// Converts a `Future` to an observable sequence. Even though if the future
 /// poll value has `Result::Err` type, also emit as a normal value, not trigger
@@ -59,7 +52,7 @@ where
   {
     let fmapped =
       (self.0).map(move |v| SharedEmitter::emit(of::OfEmitter(v), subscriber));
-    DEFAULT_RUNTIME.lock().unwrap().spawn(fmapped).unwrap();
+    spawn_default(fmapped);
   }
 }
 
@@ -101,14 +94,14 @@ where
     let fmapped = (self.0).map(move |v| {
       SharedEmitter::emit(of::ResultEmitter(v.into()), subscriber)
     });
-    DEFAULT_RUNTIME.lock().unwrap().spawn(fmapped).unwrap();
+    spawn_default(fmapped);
   }
 }
 
 #[test]
 fn smoke() {
   use futures::future;
-  use std::sync::Arc;
+  use std::sync::{Arc, Mutex};
   let res = Arc::new(Mutex::new(0));
   let c_res = res.clone();
   {
@@ -0,0 +1,228 @@
+use crate::prelude::*;
+use futures::prelude::*;
+use futures::task::{waker, ArcWake, Context, Poll};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// Converts a `Stream` to an observable sequence, which emits the items of the
+/// stream and completes when the stream ends.
+///
+/// The stream is polled on the default runtime, see
+/// [`set_default_executor`](crate::scheduler::set_default_executor), and the
+/// polling is cancelled once the subscription is unsubscribed.
+///
+/// ```rust
+/// use futures::{executor::block_on, stream, StreamExt};
+/// use rxrust::prelude::*;
+///
+/// let stream = observable::from_stream(stream::iter(0..3))
+///   .to_shared()
+///   .to_stream();
+/// let values: Vec<_> = block_on(stream.collect());
+/// assert_eq!(values, vec![Ok(0), Ok(1), Ok(2)]);
+/// ```
+pub fn from_stream<S>(stream: S) -> ObservableBase<StreamEmitter<S>>
+where
+  S: Stream + Send + 'static,
+{
+  from_stream_on(stream, Schedulers::ThreadPool)
+}
+
+/// Same as [`from_stream`], but the stream is polled on `scheduler`, each
+/// time it's woken.
+pub fn from_stream_on<S, SD>(
+  stream: S,
+  scheduler: SD,
+) -> ObservableBase<StreamEmitter<S, SD>>
+where
+  S: Stream + Send + 'static,
+{
+  ObservableBase::new(StreamEmitter { stream, scheduler })
+}
+
+#[derive(Clone)]
+pub struct StreamEmitter<S, SD = Schedulers> {
+  stream: S,
+  scheduler: SD,
+}
+
+impl<S: Stream, SD> Emitter for StreamEmitter<S, SD> {
+  type Item = S::Item;
+  type Err = ();
+}
+
+impl<S, SD> SharedEmitter for StreamEmitter<S, SD>
+where
+  S: Stream + Send + 'static,
+  SD: Scheduler + Send + Sync + 'static,
+{
+  fn emit<O>(self, subscriber: Subscriber<O, SharedSubscription>)
+  where
+    O: Observer<Self::Item, Self::Err> + Send + Sync + 'static,
+  {
+    let mut subscription = subscriber.subscription.clone();
+    let poller = Arc::new(StreamPoller {
+      scheduler: self.scheduler,
+      state: Mutex::new(Some((Box::pin(self.stream), subscriber))),
+    });
+    subscription.add(StreamPollerHandle(Some(poller.clone())));
+    ArcWake::wake_by_ref(&poller);
+  }
+}
+
+type PollerState<S, O> =
+  Option<(Pin<Box<S>>, Subscriber<O, SharedSubscription>)>;
+
+struct StreamPoller<S, O, SD> {
+  scheduler: SD,
+  // taken once the stream ends or the subscription is unsubscribed.
+  state: Mutex<PollerState<S, O>>,
+}
+
+impl<S, O, SD> StreamPoller<S, O, SD>
+where
+  S: Stream + Send + 'static,
+  O: Observer<S::Item, ()> + Send + 'static,
+  SD: Scheduler + Send + Sync + 'static,
+{
+  fn poll(self: Arc<Self>) {
+    let waker = waker(self.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut state = self.state.lock().unwrap();
+    while let Some((stream, subscriber)) = state.as_mut() {
+      if subscriber.is_closed() {
+        state.take();
+      } else {
+        match stream.as_mut().poll_next(&mut cx) {
+          Poll::Ready(Some(v)) => subscriber.next(v),
+          Poll::Ready(None) => {
+            subscriber.complete();
+            state.take();
+          }
+          Poll::Pending => break,
+        }
+      }
+    }
+  }
+}
+
+impl<S, O, SD> ArcWake for StreamPoller<S, O, SD>
+where
+  S: Stream + Send + 'static,
+  O: Observer<S::Item, ()> + Send + 'static,
+  SD: Scheduler + Send + Sync + 'static,
+{
+  fn wake_by_ref(arc_self: &Arc<Self>) {
+    arc_self.scheduler.schedule(
+      |_, poller: Arc<Self>| poller.poll(),
+      None,
+      arc_self.clone(),
+    );
+  }
+}
+
+struct StreamPollerHandle<P>(Option<Arc<P>>);
+
+impl<S, O, SD> SubscriptionLike for StreamPollerHandle<StreamPoller<S, O, SD>>
+where
+  S: Stream + Send + 'static,
+  O: Observer<S::Item, ()> + Send + 'static,
+  SD: Scheduler + Send + Sync + 'static,
+{
+  fn unsubscribe(&mut self) {
+    if let Some(poller) = self.0.take() {
+      // the stream is dropped right away, unless it's being polled, then the
+      // poller drops it once woken.
+      match poller.state.try_lock() {
+        Ok(mut state) => {
+          state.take();
+        }
+        Err(_) => ArcWake::wake_by_ref(&poller),
+      }
+    }
+  }
+
+  #[inline]
+  fn is_closed(&self) -> bool { self.0.is_none() }
+
+  #[inline]
+  fn inner_addr(&self) -> *const () { ((&self.0) as *const _) as *const () }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use futures::executor::block_on;
+  use futures::{channel::mpsc, stream, StreamExt};
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn smoke() {
+    let stream = observable::from_stream(stream::iter(0..3))
+      .to_shared()
+      .to_stream();
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, vec![Ok(0), Ok(1), Ok(2)]);
+  }
+
+  #[test]
+  fn poll_on_scheduler() {
+    let scheduler = TestScheduler::new();
+    let (sender, receiver) = mpsc::unbounded();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let completed = Arc::new(Mutex::new(false));
+    let c_completed = completed.clone();
+    observable::from_stream_on(receiver, scheduler.clone())
+      .to_shared()
+      .subscribe_complete(
+        move |v| values.lock().unwrap().push(v),
+        move || *completed.lock().unwrap() = true,
+      );
+
+    sender.unbounded_send(1).unwrap();
+    sender.unbounded_send(2).unwrap();
+    assert!(c_values.lock().unwrap().is_empty());
+    scheduler.flush();
+    assert_eq!(*c_values.lock().unwrap(), vec![1, 2]);
+
+    sender.close_channel();
+    assert!(!*c_completed.lock().unwrap());
+    scheduler.flush();
+    assert!(*c_completed.lock().unwrap());
+  }
+
+  #[test]
+  fn cancel_on_unsubscribe() {
+    let scheduler = TestScheduler::new();
+    let (sender, receiver) = mpsc::unbounded();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut subscription =
+      observable::from_stream_on(receiver, scheduler.clone())
+        .to_shared()
+        .subscribe(move |v| values.lock().unwrap().push(v));
+
+    sender.unbounded_send(1).unwrap();
+    scheduler.flush();
+    subscription.unsubscribe();
+    assert!(sender.unbounded_send(2).is_err());
+    scheduler.flush();
+    assert_eq!(*c_values.lock().unwrap(), vec![1]);
+  }
+
+  #[test]
+  fn unsubscribe_while_polled() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    observable::from_stream_on(stream::iter(0..10), scheduler.clone())
+      .take(2)
+      .to_shared()
+      .subscribe(move |v| values.lock().unwrap().push(v));
+
+    scheduler.flush();
+    assert_eq!(*c_values.lock().unwrap(), vec![0, 1]);
+    assert_eq!(scheduler.pending_size(), 0);
+  }
+}
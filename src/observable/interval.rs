@@ -1,17 +1,11 @@
-use crate::observable::from_future::DEFAULT_RUNTIME;
 use crate::prelude::*;
-use futures::prelude::*;
-use futures::{future::RemoteHandle, task::SpawnExt};
-use futures_timer::Interval;
+use futures::future::RemoteHandle;
 use std::time::{Duration, Instant};
 
 /// Creates an observable which will fire at `dur` time into the future,
 /// and will repeat every `dur` interval after.
 pub fn interval(dur: Duration) -> ObservableBase<IntervalEmitter> {
-  ObservableBase::new(IntervalEmitter {
-    dur,
-    at: Instant::now() + dur,
-  })
+  interval_at(Instant::now() + dur, dur)
 }
 
 /// Creates an observable which will fire at the time specified by `at`,
@@ -20,41 +14,112 @@ pub fn interval_at(
   at: Instant,
   dur: Duration,
 ) -> ObservableBase<IntervalEmitter> {
-  ObservableBase::new(IntervalEmitter { dur, at })
+  interval_at_on(at, dur, Schedulers::ThreadPool)
 }
 
-#[derive(Clone)]
-pub struct IntervalEmitter {
+/// Same as [`interval`], but the ticks are scheduled on `scheduler`, and the
+/// first one fires `dur` after each subscription, on the clock of
+/// `scheduler`, instead of `dur` after the observable was created.
+pub fn interval_on<SD>(
   dur: Duration,
+  scheduler: SD,
+) -> ObservableBase<IntervalEmitter<SD>> {
+  ObservableBase::new(IntervalEmitter {
+    dur,
+    at: None,
+    scheduler,
+  })
+}
+
+/// Same as [`interval_at`], but the ticks are scheduled on `scheduler`. `at`
+/// is read on the clock of `scheduler`, see
+/// [`Scheduler::now`](crate::scheduler::Scheduler::now).
+pub fn interval_at_on<SD>(
   at: Instant,
+  dur: Duration,
+  scheduler: SD,
+) -> ObservableBase<IntervalEmitter<SD>> {
+  ObservableBase::new(IntervalEmitter {
+    dur,
+    at: Some(at),
+    scheduler,
+  })
 }
-impl Emitter for IntervalEmitter {
+
+#[derive(Clone)]
+pub struct IntervalEmitter<SD = Schedulers> {
+  dur: Duration,
+  // the first tick, `dur` after the subscription if not specified.
+  at: Option<Instant>,
+  scheduler: SD,
+}
+
+impl<SD> Emitter for IntervalEmitter<SD> {
   type Item = usize;
   type Err = ();
 }
 
-impl SharedEmitter for IntervalEmitter {
+impl<SD> SharedEmitter for IntervalEmitter<SD>
+where
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
   fn emit<O>(self, subscriber: Subscriber<O, SharedSubscription>)
   where
     O: Observer<Self::Item, Self::Err> + Send + Sync + 'static,
   {
     let Subscriber {
-      mut observer,
+      observer,
       mut subscription,
     } = subscriber;
-    let mut number = 0;
-    let f = Interval::new_at(self.at, self.dur).for_each(move |_| {
-      observer.next(number);
-      number += 1;
-      future::ready(())
-    });
-    let handle = DEFAULT_RUNTIME
-      .lock()
-      .unwrap()
-      .spawn_with_handle(f)
-      .expect("spawn future for an interval failed");
+    let Self {
+      dur,
+      at,
+      scheduler,
+    } = self;
+    let at = at.unwrap_or_else(|| scheduler.now() + dur);
+    // the ticks are scheduled from `at`, so they don't drift.
+    let handle = scheduler.schedule_periodic_at(
+      at,
+      dur,
+      (observer, 0),
+      |(observer, number): &mut (O, usize)| {
+        observer.next(*number);
+        *number += 1;
+      },
+    );
+    subscription.add(handle);
+  }
+}
 
-    subscription.add(SpawnHandle::new(handle));
+impl<SD> LocalEmitter<'static> for IntervalEmitter<SD>
+where
+  SD: LocalScheduler + Clone + 'static,
+{
+  fn emit<O>(self, subscriber: Subscriber<O, LocalSubscription>)
+  where
+    O: Observer<Self::Item, Self::Err> + 'static,
+  {
+    let Subscriber {
+      observer,
+      mut subscription,
+    } = subscriber;
+    let Self {
+      dur,
+      at,
+      scheduler,
+    } = self;
+    let at = at.unwrap_or_else(|| scheduler.now() + dur);
+    // the ticks are scheduled from `at`, so they don't drift.
+    let handle = scheduler.schedule_periodic_at(
+      at,
+      dur,
+      (observer, 0),
+      |(observer, number): &mut (O, usize)| {
+        observer.next(*number);
+        *number += 1;
+      },
+    );
+    subscription.add(handle);
   }
 }
 
@@ -97,6 +162,42 @@ fn smoke() {
   assert_eq!(*c_seconds.lock().unwrap(), 5);
 }
 
+#[test]
+fn virtual_time() {
+  use std::sync::{Arc, Mutex};
+  let scheduler = TestScheduler::new();
+  let ticks = Arc::new(Mutex::new(vec![]));
+  let c_ticks = ticks.clone();
+
+  let mut subscription =
+    interval_on(Duration::from_millis(20), scheduler.clone())
+      .to_shared()
+      .subscribe(move |v| c_ticks.lock().unwrap().push(v));
+  scheduler.advance_by(Duration::from_millis(19));
+  assert!(ticks.lock().unwrap().is_empty());
+  scheduler.advance_by(Duration::from_millis(41));
+  assert_eq!(*ticks.lock().unwrap(), vec![0, 1, 2]);
+
+  subscription.unsubscribe();
+  scheduler.advance_by(Duration::from_millis(100));
+  assert_eq!(*ticks.lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn local() {
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  let scheduler = LocalPoolScheduler::new();
+  let ticks = Rc::new(RefCell::new(vec![]));
+  let c_ticks = ticks.clone();
+
+  interval_on(Duration::from_millis(10), scheduler.clone())
+    .take(3)
+    .subscribe(move |v| c_ticks.borrow_mut().push(v));
+  scheduler.run();
+  assert_eq!(*ticks.borrow(), vec![0, 1, 2]);
+}
+
 #[test]
 fn smoke_fork() {
   interval(Duration::from_millis(10))
@@ -0,0 +1,208 @@
+use crate::prelude::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+pub trait ToFuture<Item, Err> {
+  /// Subscribes the observable and returns a `Future` resolving with its first
+  /// item, or `None` if it completes without emitting, or its error. The
+  /// observable is unsubscribed once the first item is received, or when the
+  /// future is dropped.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use futures::executor::block_on;
+  /// use rxrust::prelude::*;
+  ///
+  /// let first = observable::from_iter(1..4).to_shared().to_future();
+  /// assert_eq!(block_on(first), Ok(Some(1)));
+  /// ```
+  fn to_future(self) -> ObservableFuture<Option<Item>, Err>;
+
+  /// Same as [`to_future`](ToFuture::to_future), but resolves with the last
+  /// item once the observable completes.
+  fn last_future(self) -> ObservableFuture<Option<Item>, Err>;
+
+  /// Same as [`to_future`](ToFuture::to_future), but resolves with all the
+  /// items once the observable completes.
+  fn collect_future(self) -> ObservableFuture<Vec<Item>, Err>;
+}
+
+struct FutureState<T, Err> {
+  acc: Option<T>,
+  result: Option<Result<T, Err>>,
+  waker: Option<Waker>,
+}
+
+/// A `Future` resolving with the result of an observable, see [`ToFuture`].
+/// Dropping it unsubscribes the observable.
+pub struct ObservableFuture<T, Err> {
+  state: Arc<Mutex<FutureState<T, Err>>>,
+  subscription: SharedSubscription,
+}
+
+impl<T, Err> Future for ObservableFuture<T, Err> {
+  type Output = Result<T, Err>;
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+    let mut state = self.state.lock().unwrap();
+    match state.result.take() {
+      Some(result) => Poll::Ready(result),
+      None => {
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+    }
+  }
+}
+
+impl<T, Err> Drop for ObservableFuture<T, Err> {
+  fn drop(&mut self) { self.subscription.unsubscribe(); }
+}
+
+pub struct FutureObserver<T, Item, Err> {
+  state: Arc<Mutex<FutureState<T, Err>>>,
+  // accumulates an item, returns true if the future can be resolved.
+  fold: fn(&mut T, Item) -> bool,
+  subscription: SharedSubscription,
+}
+
+impl<T, Item, Err> FutureObserver<T, Item, Err> {
+  fn resolve(&mut self, result: impl FnOnce(T) -> Result<T, Err>) {
+    let waker = {
+      let mut state = self.state.lock().unwrap();
+      match state.acc.take() {
+        Some(acc) => {
+          state.result = Some(result(acc));
+          state.waker.take()
+        }
+        None => None,
+      }
+    };
+    self.subscription.unsubscribe();
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+  }
+}
+
+impl<T, Item, Err> Observer<Item, Err> for FutureObserver<T, Item, Err> {
+  fn next(&mut self, value: Item) {
+    let done = match self.state.lock().unwrap().acc.as_mut() {
+      Some(acc) => (self.fold)(acc, value),
+      None => false,
+    };
+    if done {
+      self.resolve(Ok);
+    }
+  }
+
+  fn error(&mut self, err: Err) { self.resolve(move |_| Err(err)); }
+
+  fn complete(&mut self) { self.resolve(Ok); }
+}
+
+fn subscribe_future<S, T>(
+  source: S,
+  init: T,
+  fold: fn(&mut T, S::Item) -> bool,
+) -> ObservableFuture<T, S::Err>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+  T: Send + 'static,
+{
+  let state = Arc::new(Mutex::new(FutureState {
+    acc: Some(init),
+    result: None,
+    waker: None,
+  }));
+  let subscription = SharedSubscription::default();
+  let observer = FutureObserver {
+    state: state.clone(),
+    fold,
+    subscription: subscription.clone(),
+  };
+  source.actual_subscribe(Subscriber {
+    observer,
+    subscription: subscription.clone(),
+  });
+  ObservableFuture {
+    state,
+    subscription,
+  }
+}
+
+impl<S> ToFuture<S::Item, S::Err> for Shared<S>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+{
+  fn to_future(self) -> ObservableFuture<Option<S::Item>, S::Err> {
+    subscribe_future(self.0, None, |acc, v| {
+      *acc = Some(v);
+      true
+    })
+  }
+
+  fn last_future(self) -> ObservableFuture<Option<S::Item>, S::Err> {
+    subscribe_future(self.0, None, |acc, v| {
+      *acc = Some(v);
+      false
+    })
+  }
+
+  fn collect_future(self) -> ObservableFuture<Vec<S::Item>, S::Err> {
+    subscribe_future(self.0, vec![], |acc, v| {
+      acc.push(v);
+      false
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use futures::executor::block_on;
+  use std::time::Duration;
+
+  #[test]
+  fn first_last_collect() {
+    let source = observable::from_iter(1..4).to_shared();
+    assert_eq!(block_on(source.clone().to_future()), Ok(Some(1)));
+    assert_eq!(block_on(source.clone().last_future()), Ok(Some(3)));
+    assert_eq!(block_on(source.collect_future()), Ok(vec![1, 2, 3]));
+  }
+
+  #[test]
+  fn empty() {
+    let source = observable::empty::<i32>().to_shared();
+    assert_eq!(block_on(source.clone().to_future()), Ok(None));
+    assert_eq!(block_on(source.clone().last_future()), Ok(None));
+    assert_eq!(block_on(source.collect_future()), Ok(vec![]));
+  }
+
+  #[test]
+  fn error() {
+    let source = observable::throw("error").to_shared();
+    assert_eq!(block_on(source.clone().to_future()), Err("error"));
+    assert_eq!(block_on(source.collect_future()), Err("error"));
+  }
+
+  #[test]
+  fn async_source() {
+    let ticks = observable::interval(Duration::from_millis(1))
+      .take(3)
+      .to_shared()
+      .collect_future();
+    assert_eq!(block_on(ticks), Ok(vec![0, 1, 2]));
+
+    let first = observable::interval(Duration::from_millis(1))
+      .to_shared()
+      .to_future();
+    assert_eq!(block_on(first), Ok(Some(0)));
+  }
+}
@@ -0,0 +1,325 @@
+use crate::prelude::*;
+use futures::stream::Stream;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+pub trait ToStream<Item, Err> {
+  type Stream: Stream<Item = Result<Item, Err>>;
+
+  /// Subscribes the observable and converts it to a `Stream`, which yields the
+  /// items as `Ok`, and the error as a last `Err`. The stream ends when the
+  /// observable completes or errors, and unsubscribes the observable when
+  /// it's dropped.
+  ///
+  /// The buffer of the stream is **unbounded**: an observable can't be
+  /// paused, so the notifications are buffered until the stream is polled,
+  /// and the buffer grows without limit while the consumer falls behind. Use
+  /// [`to_stream_dropping_oldest`](ToStream::to_stream_dropping_oldest) for a
+  /// bounded buffer, which drops the oldest items once it's full.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use futures::{executor::block_on, StreamExt};
+  /// use rxrust::prelude::*;
+  ///
+  /// let stream = observable::from_iter(0..3).to_stream();
+  /// let values: Vec<_> = block_on(stream.collect());
+  /// assert_eq!(values, vec![Ok(0), Ok(1), Ok(2)]);
+  /// ```
+  #[inline]
+  fn to_stream(self) -> Self::Stream
+  where
+    Self: Sized,
+  {
+    self.to_stream_with_buffer(StreamBuffer::new(None))
+  }
+
+  /// Same as [`to_stream`](ToStream::to_stream), but buffers at most
+  /// `capacity` items. When the consumer of the stream falls behind and the
+  /// buffer is full, the oldest buffered item is **dropped** for the new one,
+  /// so the stream only yields the latest `capacity` items. The error, if
+  /// any, is never dropped.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use futures::{executor::block_on, StreamExt};
+  /// use rxrust::prelude::*;
+  ///
+  /// let stream = observable::from_iter(0..5).to_stream_dropping_oldest(2);
+  /// let values: Vec<_> = block_on(stream.collect());
+  /// assert_eq!(values, vec![Ok(3), Ok(4)]);
+  /// ```
+  ///
+  /// # Panics
+  ///
+  /// Panics if `capacity` is 0.
+  #[inline]
+  fn to_stream_dropping_oldest(self, capacity: usize) -> Self::Stream
+  where
+    Self: Sized,
+  {
+    assert!(capacity > 0, "the capacity of a stream can't be 0.");
+    self.to_stream_with_buffer(StreamBuffer::new(Some(capacity)))
+  }
+
+  #[doc(hidden)]
+  fn to_stream_with_buffer(
+    self,
+    buffer: StreamBuffer<Item, Err>,
+  ) -> Self::Stream;
+}
+
+pub struct StreamBuffer<Item, Err> {
+  values: VecDeque<Result<Item, Err>>,
+  // `None` for an unbounded buffer.
+  capacity: Option<usize>,
+  done: bool,
+  waker: Option<Waker>,
+}
+
+impl<Item, Err> StreamBuffer<Item, Err> {
+  fn new(capacity: Option<usize>) -> Self {
+    StreamBuffer {
+      values: VecDeque::new(),
+      capacity,
+      done: false,
+      waker: None,
+    }
+  }
+
+  fn push(&mut self, value: Result<Item, Err>) -> Option<Waker> {
+    if !self.done {
+      if Some(self.values.len()) == self.capacity {
+        self.values.pop_front();
+      }
+      self.values.push_back(value);
+    }
+    self.waker.take()
+  }
+
+  fn finish(&mut self) -> Option<Waker> {
+    self.done = true;
+    self.waker.take()
+  }
+
+  fn poll(&mut self, cx: &mut Context) -> Poll<Option<Result<Item, Err>>> {
+    if let Some(value) = self.values.pop_front() {
+      Poll::Ready(Some(value))
+    } else if self.done {
+      Poll::Ready(None)
+    } else {
+      self.waker = Some(cx.waker().clone());
+      Poll::Pending
+    }
+  }
+}
+
+pub struct ObservableStream<B, U: SubscriptionLike> {
+  buffer: B,
+  subscription: U,
+}
+
+impl<B, U: SubscriptionLike> Drop for ObservableStream<B, U> {
+  fn drop(&mut self) { self.subscription.unsubscribe(); }
+}
+
+impl<Item, Err, U> Stream
+  for ObservableStream<Rc<RefCell<StreamBuffer<Item, Err>>>, U>
+where
+  U: SubscriptionLike,
+{
+  type Item = Result<Item, Err>;
+  fn poll_next(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<Option<Self::Item>> {
+    self.buffer.borrow_mut().poll(cx)
+  }
+}
+
+impl<Item, Err, U> Stream
+  for ObservableStream<Arc<Mutex<StreamBuffer<Item, Err>>>, U>
+where
+  U: SubscriptionLike,
+{
+  type Item = Result<Item, Err>;
+  fn poll_next(
+    self: Pin<&mut Self>,
+    cx: &mut Context,
+  ) -> Poll<Option<Self::Item>> {
+    self.buffer.lock().unwrap().poll(cx)
+  }
+}
+
+pub struct StreamObserver<B>(B);
+
+impl<Item, Err> Observer<Item, Err>
+  for StreamObserver<Rc<RefCell<StreamBuffer<Item, Err>>>>
+{
+  fn next(&mut self, value: Item) {
+    let waker = self.0.borrow_mut().push(Ok(value));
+    wake(waker);
+  }
+
+  fn error(&mut self, err: Err) {
+    let waker = {
+      let mut buffer = self.0.borrow_mut();
+      buffer.push(Err(err));
+      buffer.finish()
+    };
+    wake(waker);
+  }
+
+  fn complete(&mut self) {
+    let waker = self.0.borrow_mut().finish();
+    wake(waker);
+  }
+}
+
+impl<Item, Err> Observer<Item, Err>
+  for StreamObserver<Arc<Mutex<StreamBuffer<Item, Err>>>>
+{
+  fn next(&mut self, value: Item) {
+    let waker = self.0.lock().unwrap().push(Ok(value));
+    wake(waker);
+  }
+
+  fn error(&mut self, err: Err) {
+    let waker = {
+      let mut buffer = self.0.lock().unwrap();
+      buffer.push(Err(err));
+      buffer.finish()
+    };
+    wake(waker);
+  }
+
+  fn complete(&mut self) {
+    let waker = self.0.lock().unwrap().finish();
+    wake(waker);
+  }
+}
+
+// Wakes the task polling the stream, once the buffer is released.
+#[inline]
+fn wake(waker: Option<Waker>) {
+  if let Some(waker) = waker {
+    waker.wake();
+  }
+}
+
+impl<S> ToStream<S::Item, S::Err> for S
+where
+  S: LocalObservable<'static>,
+  S::Item: 'static,
+  S::Err: 'static,
+{
+  type Stream =
+    ObservableStream<Rc<RefCell<StreamBuffer<S::Item, S::Err>>>, S::Unsub>;
+  fn to_stream_with_buffer(
+    self,
+    buffer: StreamBuffer<S::Item, S::Err>,
+  ) -> Self::Stream {
+    let buffer = Rc::new(RefCell::new(buffer));
+    let subscription =
+      self.actual_subscribe(Subscriber::local(StreamObserver(buffer.clone())));
+    ObservableStream {
+      buffer,
+      subscription,
+    }
+  }
+}
+
+impl<S> ToStream<S::Item, S::Err> for Shared<S>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+{
+  type Stream =
+    ObservableStream<Arc<Mutex<StreamBuffer<S::Item, S::Err>>>, S::Unsub>;
+  fn to_stream_with_buffer(
+    self,
+    buffer: StreamBuffer<S::Item, S::Err>,
+  ) -> Self::Stream {
+    let buffer = Arc::new(Mutex::new(buffer));
+    let subscription = self
+      .0
+      .actual_subscribe(Subscriber::shared(StreamObserver(buffer.clone())));
+    ObservableStream {
+      buffer,
+      subscription,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use futures::executor::block_on;
+  use futures::StreamExt;
+  use std::time::Duration;
+
+  #[test]
+  fn local() {
+    let stream = observable::from_iter(0..3).to_stream();
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, vec![Ok(0), Ok(1), Ok(2)]);
+  }
+
+  #[test]
+  fn error() {
+    let stream = observable::create(|mut subscriber| {
+      subscriber.next(1);
+      subscriber.error("error");
+      subscriber.next(2);
+    })
+    .to_stream();
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, vec![Ok(1), Err("error")]);
+  }
+
+  #[test]
+  fn unbounded() {
+    let stream = observable::from_iter(0..2000).to_stream();
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, (0..2000).map(Ok).collect::<Vec<Result<_, ()>>>());
+  }
+
+  #[test]
+  fn dropping_oldest() {
+    let stream = observable::from_iter(0..5).to_stream_dropping_oldest(2);
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, vec![Ok(3), Ok(4)]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn zero_capacity() { observable::of(1).to_stream_dropping_oldest(0); }
+
+  #[test]
+  fn shared() {
+    let stream = observable::interval(Duration::from_millis(1))
+      .take(3)
+      .to_shared()
+      .to_stream();
+    let values: Vec<_> = block_on(stream.collect());
+    assert_eq!(values, vec![Ok(0), Ok(1), Ok(2)]);
+  }
+
+  #[test]
+  fn unsubscribe_when_dropped() {
+    let mut subject: LocalSubject<'static, i32, ()> = Subject::new();
+    let stream = subject.clone().to_stream();
+    assert_eq!(subject.subscribed_size(), 1);
+    subject.next(1);
+    drop(stream);
+    subject.next(2);
+    assert_eq!(subject.subscribed_size(), 0);
+  }
+}
@@ -3,6 +3,9 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+mod channel;
+pub use channel::SinkObserver;
+
 /// An Observer is a consumer of values delivered by an Observable. One for each
 /// type of notification delivered by the Observable: `next`, `error`,
 /// and `complete`.
@@ -127,9 +130,9 @@ where
   Err: PayloadCopy,
 {
   fn next(&mut self, value: Item) {
-    self.drain_filter(|subscriber| {
+    self.retain_mut(|subscriber| {
       subscriber.next(value.payload_copy());
-      subscriber.is_closed()
+      !subscriber.is_closed()
     });
   }
 
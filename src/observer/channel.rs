@@ -0,0 +1,292 @@
+use crate::prelude::*;
+use crate::scheduler::spawn_default;
+use futures::channel::mpsc as futures_mpsc;
+use futures::executor::block_on;
+use futures::future::poll_fn;
+use futures::{FutureExt, Sink, StreamExt};
+use std::sync::mpsc;
+
+// The channel senders forward each notification as a `Notification`, the
+// notifications are dropped if the receiver is disconnected.
+
+/// Disconnects from the receiver after the error or the completion.
+impl<Item, Err> Observer<Item, Err> for mpsc::Sender<Notification<Item, Err>> {
+  fn next(&mut self, value: Item) {
+    let _ = self.send(Notification::Next(value));
+  }
+
+  fn error(&mut self, err: Err) {
+    let _ = self.send(Notification::Error(err));
+    // replacing the sender drops it.
+    *self = mpsc::channel().0;
+  }
+
+  fn complete(&mut self) {
+    let _ = self.send(Notification::Complete);
+    *self = mpsc::channel().0;
+  }
+}
+
+/// Disconnects from the receiver after the error or the completion.
+///
+/// Sending blocks the emitting thread while the channel is full, so the
+/// receiver must not be drained by the emitting thread.
+impl<Item, Err> Observer<Item, Err>
+  for mpsc::SyncSender<Notification<Item, Err>>
+{
+  fn next(&mut self, value: Item) {
+    let _ = self.send(Notification::Next(value));
+  }
+
+  fn error(&mut self, err: Err) {
+    let _ = self.send(Notification::Error(err));
+    *self = mpsc::sync_channel(0).0;
+  }
+
+  fn complete(&mut self) {
+    let _ = self.send(Notification::Complete);
+    *self = mpsc::sync_channel(0).0;
+  }
+}
+
+/// Disconnects from the receiver after the error or the completion.
+impl<Item, Err> Observer<Item, Err>
+  for futures_mpsc::UnboundedSender<Notification<Item, Err>>
+{
+  fn next(&mut self, value: Item) {
+    let _ = self.unbounded_send(Notification::Next(value));
+  }
+
+  fn error(&mut self, err: Err) {
+    let _ = self.unbounded_send(Notification::Error(err));
+    self.disconnect();
+  }
+
+  fn complete(&mut self) {
+    let _ = self.unbounded_send(Notification::Complete);
+    self.disconnect();
+  }
+}
+
+/// Disconnects from the receiver after the error or the completion.
+///
+/// Sending blocks the emitting thread while the channel is full, so the
+/// receiver must not be polled by the emitting thread.
+impl<Item, Err> Observer<Item, Err>
+  for futures_mpsc::Sender<Notification<Item, Err>>
+{
+  fn next(&mut self, value: Item) {
+    send_blocking(self, Notification::Next(value));
+  }
+
+  fn error(&mut self, err: Err) {
+    send_blocking(self, Notification::Error(err));
+    self.disconnect();
+  }
+
+  fn complete(&mut self) {
+    send_blocking(self, Notification::Complete);
+    self.disconnect();
+  }
+}
+
+fn send_blocking<T>(sender: &mut futures_mpsc::Sender<T>, msg: T) {
+  // waits for a slot, flushing would wait for the receiver too.
+  if block_on(poll_fn(|cx| sender.poll_ready(cx))).is_ok() {
+    let _ = sender.start_send(msg);
+  }
+}
+
+/// An Observer forwarding the notifications to a `futures::Sink` of
+/// `Notification`s, and closing it after the error or the completion.
+///
+/// Sending never blocks the emitting thread: the notifications are buffered
+/// in an unbounded channel, and a task on the default runtime, see
+/// [`set_default_executor`](crate::scheduler::set_default_executor), sends
+/// them to the sink in order, then closes it.
+///
+/// # Example
+///
+/// ```
+/// use futures::{channel::mpsc, executor::block_on, StreamExt};
+/// use rxrust::prelude::*;
+///
+/// let (sender, receiver) = mpsc::unbounded();
+/// LocalObservable::actual_subscribe(
+///   observable::from_iter(0..2),
+///   Subscriber::local(SinkObserver::new(sender)),
+/// );
+///
+/// let notifications: Vec<Notification<_, ()>> = block_on(receiver.collect());
+/// assert_eq!(
+///   notifications,
+///   vec![
+///     Notification::Next(0),
+///     Notification::Next(1),
+///     Notification::Complete
+///   ]
+/// );
+/// ```
+#[derive(Clone)]
+pub struct SinkObserver<Item, Err>(
+  futures_mpsc::UnboundedSender<Notification<Item, Err>>,
+);
+
+impl<Item, Err> SinkObserver<Item, Err> {
+  pub fn new<S>(sink: S) -> Self
+  where
+    S: Sink<Notification<Item, Err>> + Send + 'static,
+    Item: Send + 'static,
+    Err: Send + 'static,
+  {
+    let (sender, receiver) = futures_mpsc::unbounded();
+    spawn_default(receiver.map(Ok).forward(sink).map(|_| ()));
+    SinkObserver(sender)
+  }
+}
+
+impl<Item, Err> Observer<Item, Err> for SinkObserver<Item, Err> {
+  #[inline]
+  fn next(&mut self, value: Item) { self.0.next(value); }
+
+  #[inline]
+  fn error(&mut self, err: Err) { self.0.error(err); }
+
+  #[inline]
+  fn complete(&mut self) { self.0.complete(); }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use futures::{channel::mpsc as futures_mpsc, executor::block_on, StreamExt};
+  use std::sync::{mpsc, Arc, Mutex};
+  use std::thread;
+
+  #[test]
+  fn std_sender() {
+    let (sender, receiver) = mpsc::channel();
+    LocalObservable::actual_subscribe(
+      observable::from_iter(0..2),
+      Subscriber::local(sender),
+    );
+
+    assert_eq!(
+      receiver.iter().collect::<Vec<_>>(),
+      vec![
+        Notification::Next(0),
+        Notification::Next(1),
+        Notification::<_, ()>::Complete
+      ]
+    );
+  }
+
+  #[test]
+  fn std_sender_disconnect_on_complete() {
+    let (sender, receiver) = mpsc::channel();
+    let mut subject = Subject::new();
+    LocalObservable::actual_subscribe(
+      subject.clone(),
+      Subscriber::local(sender),
+    );
+    subject.next(1);
+    subject.complete();
+
+    // the subject still holds the observer, the receiver ends anyway.
+    assert_eq!(
+      receiver.iter().collect::<Vec<_>>(),
+      vec![Notification::Next(1), Notification::<_, ()>::Complete]
+    );
+  }
+
+  #[test]
+  fn shared_sync_sender() {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    SharedObservable::actual_subscribe(
+      observable::throw("error").to_shared(),
+      Subscriber::shared(Arc::new(Mutex::new(sender))),
+    );
+
+    assert_eq!(
+      receiver.iter().collect::<Vec<_>>(),
+      vec![Notification::<(), _>::Error("error")]
+    );
+  }
+
+  #[test]
+  fn futures_sender() {
+    let (sender, receiver) = futures_mpsc::channel(3);
+    let mut subject = Subject::new();
+    LocalObservable::actual_subscribe(
+      subject.clone(),
+      Subscriber::local(sender),
+    );
+    (0..3).for_each(|v| subject.next(v));
+    subject.complete();
+
+    assert_eq!(
+      block_on(receiver.collect::<Vec<_>>()),
+      vec![
+        Notification::Next(0),
+        Notification::Next(1),
+        Notification::Next(2),
+        Notification::<_, ()>::Complete
+      ]
+    );
+  }
+
+  #[test]
+  fn futures_sender_full() {
+    // a single slot, the emitting thread waits for the receiver.
+    let (sender, receiver) = futures_mpsc::channel(0);
+    let handle = thread::spawn(move || {
+      LocalObservable::actual_subscribe(
+        observable::from_iter(0..3),
+        Subscriber::local(sender),
+      );
+    });
+
+    assert_eq!(
+      block_on(receiver.collect::<Vec<_>>()),
+      vec![
+        Notification::Next(0),
+        Notification::Next(1),
+        Notification::Next(2),
+        Notification::<_, ()>::Complete
+      ]
+    );
+    handle.join().unwrap();
+  }
+
+  #[test]
+  fn sink() {
+    let (sender, receiver) = futures_mpsc::channel(2);
+    let mut subject = Subject::new();
+    LocalObservable::actual_subscribe(
+      subject.clone(),
+      Subscriber::local(SinkObserver::new(sender)),
+    );
+    subject.next(1);
+    subject.error("error");
+
+    assert_eq!(
+      block_on(receiver.collect::<Vec<_>>()),
+      vec![Notification::Next(1), Notification::Error("error")]
+    );
+  }
+
+  #[test]
+  fn sink_full() {
+    // the sink is slower than the source, nothing is dropped.
+    let (sender, receiver) = futures_mpsc::channel(0);
+    LocalObservable::actual_subscribe(
+      observable::from_iter(0..10),
+      Subscriber::local(SinkObserver::new(sender)),
+    );
+
+    let notifications = block_on(receiver.collect::<Vec<_>>());
+    assert_eq!(notifications.len(), 11);
+    assert_eq!(notifications[9], Notification::Next(9));
+    assert_eq!(notifications[10], Notification::<_, ()>::Complete);
+  }
+}
@@ -1,23 +1,34 @@
+pub mod catch_error;
+pub mod combine_latest;
+pub mod concat;
 pub mod default_if_empty;
 pub mod delay;
+pub mod exhaust_map;
 pub mod filter;
 pub mod filter_map;
 pub mod first;
+pub mod flat_map;
 pub mod last;
 pub mod map;
+pub mod map_err;
 pub mod merge;
 pub mod observe_on;
 pub mod ref_count;
+pub mod retry;
+pub mod retry_with_backoff;
 pub mod sample;
 pub mod scan;
 pub mod skip;
 pub mod skip_last;
 pub mod subscribe_on;
+pub mod switch_map;
 pub mod take;
 pub mod take_last;
 pub mod take_until;
 pub mod take_while;
 pub mod throttle_time;
+pub mod timeout;
+pub mod with_latest_from;
 pub use filter_map::FilterMap;
 pub mod box_it;
 pub mod zip;
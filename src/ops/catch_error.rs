@@ -0,0 +1,202 @@
+use crate::prelude::*;
+
+/// An Observable that mirrors the source, and continues with the observable
+/// returned by the handler if the source emits an error.
+///
+/// This struct is created by the catch_error method on
+/// [Observable](Observable::catch_error). See its documentation for more.
+#[derive(Clone)]
+pub struct CatchErrorOp<S, F> {
+  pub(crate) source: S,
+  pub(crate) func: F,
+}
+
+impl<S, F, Inner> Observable for CatchErrorOp<S, F>
+where
+  S: Observable,
+  F: FnOnce(S::Err) -> Inner,
+  Inner: Observable<Item = S::Item>,
+{
+  type Item = S::Item;
+  type Err = Inner::Err;
+}
+
+#[doc(hidden)]
+macro observable_impl($subscription:ty, $($marker:ident +)* $lf: lifetime) {
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + $($marker +)* $lf>(
+    self,
+    subscriber: Subscriber<O, $subscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    subscription.add(self.source.actual_subscribe(Subscriber {
+      observer: CatchErrorObserver {
+        observer: Some(subscriber.observer),
+        func: Some(self.func),
+        subscription: subscription.clone(),
+      },
+      subscription: <$subscription>::default(),
+    }));
+    subscription
+  }
+}
+
+impl<'a, S, F, Inner> LocalObservable<'a> for CatchErrorOp<S, F>
+where
+  S: LocalObservable<'a>,
+  F: FnOnce(S::Err) -> Inner + 'a,
+  Inner: LocalObservable<'a, Item = S::Item>,
+{
+  type Unsub = LocalSubscription;
+  observable_impl!(LocalSubscription, 'a);
+}
+
+impl<S, F, Inner> SharedObservable for CatchErrorOp<S, F>
+where
+  S: SharedObservable,
+  S::Unsub: Send + Sync,
+  F: FnOnce(S::Err) -> Inner + Send + Sync + 'static,
+  Inner: SharedObservable<Item = S::Item>,
+  Inner::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  observable_impl!(SharedSubscription, Send + Sync + 'static);
+}
+
+pub struct CatchErrorObserver<O, F, U> {
+  // the observer is handed over to the fallback observable once the source
+  // emits an error.
+  observer: Option<O>,
+  func: Option<F>,
+  subscription: U,
+}
+
+#[doc(hidden)]
+macro observer_impl($item: ident, $err: ident, $subscription: ty) {
+  fn next(&mut self, value: $item) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    if let (Some(observer), Some(func)) =
+      (self.observer.take(), self.func.take())
+    {
+      self.subscription.add(func(err).actual_subscribe(Subscriber {
+        observer,
+        subscription: <$subscription>::default(),
+      }));
+    }
+  }
+
+  fn complete(&mut self) {
+    if let Some(mut observer) = self.observer.take() {
+      observer.complete();
+      self.subscription.unsubscribe();
+    }
+  }
+}
+
+impl<'a, Item, Err, O, F, Inner> Observer<Item, Err>
+  for CatchErrorObserver<O, F, LocalSubscription>
+where
+  O: Observer<Item, Inner::Err> + 'a,
+  F: FnOnce(Err) -> Inner,
+  Inner: LocalObservable<'a, Item = Item>,
+{
+  observer_impl!(Item, Err, LocalSubscription);
+}
+
+impl<Item, Err, O, F, Inner> Observer<Item, Err>
+  for CatchErrorObserver<O, F, SharedSubscription>
+where
+  O: Observer<Item, Inner::Err> + Send + Sync + 'static,
+  F: FnOnce(Err) -> Inner,
+  Inner: SharedObservable<Item = Item>,
+  Inner::Unsub: Send + Sync,
+{
+  observer_impl!(Item, Err, SharedSubscription);
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn recover() {
+    let mut values = vec![];
+    let mut completed = 0;
+    observable::create(|mut subscriber| {
+      subscriber.next(0);
+      subscriber.next(1);
+      subscriber.error("error");
+      subscriber.next(2);
+    })
+    .catch_error(|_| observable::from_iter(3..5))
+    .subscribe_complete(|v| values.push(v), || completed += 1);
+
+    assert_eq!(values, vec![0, 1, 3, 4]);
+    assert_eq!(completed, 1);
+  }
+
+  #[test]
+  fn handler_get_error() {
+    let mut errors = vec![];
+    observable::throw("first")
+      .catch_error(|err| observable::throw((err, "second")))
+      .subscribe_err(|_| {}, |err| errors.push(err));
+
+    assert_eq!(errors, vec![("first", "second")]);
+  }
+
+  #[test]
+  fn no_error() {
+    let mut values = vec![];
+    observable::from_iter(0..3)
+      .catch_error(|_| observable::empty())
+      .subscribe(|v| values.push(v));
+
+    assert_eq!(values, vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn unsubscribe_source() {
+    let mut values = vec![];
+    {
+      let mut source = Subject::new();
+      let mut fallback = Subject::new();
+      let c_fallback = fallback.clone();
+      source
+        .clone()
+        .catch_error(move |_: &str| c_fallback)
+        .subscribe(|v| values.push(v));
+
+      source.next(1);
+      source.error("error");
+      assert_eq!(source.subscribed_size(), 0);
+      source.next(2);
+      fallback.next(3);
+    }
+    assert_eq!(values, vec![1, 3]);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    observable::of_result(Err("error"))
+      .catch_error(|_| observable::of(1))
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    assert_eq!(*values.lock().unwrap(), vec![1]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let c = observable::of_result(Err(())).catch_error(|_| observable::of(1));
+    c.clone().catch_error(|_| observable::of(2)).subscribe(|_| {});
+    c.to_shared().subscribe(|_| {});
+  }
+}
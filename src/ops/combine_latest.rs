@@ -0,0 +1,460 @@
+use crate::prelude::*;
+use observer::{complete_proxy_impl, error_proxy_impl};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// An Observable that combines the latest items of two other Observables.
+///
+/// This struct is created by the combine_latest method on
+/// [Observable](Observable::combine_latest). See its documentation for more.
+#[derive(Clone)]
+pub struct CombineLatestOp<A, B, F> {
+  pub(crate) a: A,
+  pub(crate) b: B,
+  pub(crate) binary_op: F,
+}
+
+impl<A, B, F, Out> Observable for CombineLatestOp<A, B, F>
+where
+  A: Observable,
+  B: Observable<Err = A::Err>,
+  F: FnMut(A::Item, B::Item) -> Out,
+{
+  type Item = Out;
+  type Err = A::Err;
+}
+
+impl<'a, A, B, F, Out> LocalObservable<'a> for CombineLatestOp<A, B, F>
+where
+  A: LocalObservable<'a>,
+  B: LocalObservable<'a, Err = A::Err>,
+  A::Item: Clone + 'a,
+  B::Item: Clone + 'a,
+  F: FnMut(A::Item, B::Item) -> Out + 'a,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut sub = subscriber.subscription;
+    let o_combine = CombineLatestObserver::new(
+      subscriber.observer,
+      sub.clone(),
+      self.binary_op,
+    );
+    let o_combine = Rc::new(RefCell::new(o_combine));
+    sub.add(self.a.actual_subscribe(Subscriber {
+      observer: AObserver(o_combine.clone(), PhantomData),
+      subscription: LocalSubscription::default(),
+    }));
+
+    sub.add(self.b.actual_subscribe(Subscriber {
+      observer: BObserver(o_combine, PhantomData),
+      subscription: LocalSubscription::default(),
+    }));
+    sub
+  }
+}
+
+impl<A, B, F, Out> SharedObservable for CombineLatestOp<A, B, F>
+where
+  A: SharedObservable,
+  B: SharedObservable<Err = A::Err>,
+  A::Item: Clone + Send + Sync + 'static,
+  B::Item: Clone + Send + Sync + 'static,
+  A::Unsub: Send + Sync,
+  B::Unsub: Send + Sync,
+  F: FnMut(A::Item, B::Item) -> Out + Send + Sync + 'static,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut sub = subscriber.subscription;
+    let o_combine = CombineLatestObserver::new(
+      subscriber.observer,
+      sub.clone(),
+      self.binary_op,
+    );
+    let o_combine = Arc::new(Mutex::new(o_combine));
+    sub.add(self.a.actual_subscribe(Subscriber {
+      observer: AObserver(o_combine.clone(), PhantomData),
+      subscription: SharedSubscription::default(),
+    }));
+
+    sub.add(self.b.actual_subscribe(Subscriber {
+      observer: BObserver(o_combine, PhantomData),
+      subscription: SharedSubscription::default(),
+    }));
+    sub
+  }
+}
+
+enum CombineItem<A, B> {
+  ItemA(A),
+  ItemB(B),
+}
+
+struct CombineLatestObserver<O, U, F, A, B> {
+  observer: O,
+  subscription: U,
+  binary_op: F,
+  a: Option<A>,
+  b: Option<B>,
+  completed_one: bool,
+}
+
+impl<O, U, F, A, B> CombineLatestObserver<O, U, F, A, B> {
+  fn new(o: O, u: U, binary_op: F) -> Self {
+    CombineLatestObserver {
+      observer: o,
+      subscription: u,
+      binary_op,
+      a: None,
+      b: None,
+      completed_one: false,
+    }
+  }
+}
+
+impl<O, U, F, A, B, Err, Out> Observer<CombineItem<A, B>, Err>
+  for CombineLatestObserver<O, U, F, A, B>
+where
+  O: Observer<Out, Err>,
+  U: SubscriptionLike,
+  F: FnMut(A, B) -> Out,
+  A: Clone,
+  B: Clone,
+{
+  fn next(&mut self, value: CombineItem<A, B>) {
+    match value {
+      CombineItem::ItemA(v) => self.a = Some(v),
+      CombineItem::ItemB(v) => self.b = Some(v),
+    }
+    if let (Some(a), Some(b)) = (&self.a, &self.b) {
+      let v = (self.binary_op)(a.clone(), b.clone());
+      self.observer.next(v);
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    self.observer.error(err);
+    self.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    if self.completed_one {
+      self.subscription.unsubscribe();
+      self.observer.complete();
+    } else {
+      self.completed_one = true;
+    }
+  }
+}
+
+struct AObserver<O, B>(O, PhantomData<B>);
+
+impl<O, A, B, Err> Observer<A, Err> for AObserver<O, B>
+where
+  O: Observer<CombineItem<A, B>, Err>,
+{
+  fn next(&mut self, value: A) { self.0.next(CombineItem::ItemA(value)); }
+
+  error_proxy_impl!(Err, 0);
+  complete_proxy_impl!(0);
+}
+
+struct BObserver<O, A>(O, PhantomData<A>);
+
+impl<O, A, B, Err> Observer<B, Err> for BObserver<O, A>
+where
+  O: Observer<CombineItem<A, B>, Err>,
+{
+  fn next(&mut self, value: B) { self.0.next(CombineItem::ItemB(value)); }
+
+  error_proxy_impl!(Err, 0);
+  complete_proxy_impl!(0);
+}
+
+/// Combines the latest items of all the observables given. Emits a `Vec` of
+/// the latest item of every source whenever any source emits, once all of
+/// them have emitted at least once.
+///
+/// Completes when all the sources complete. Emits error when any source
+/// emits it.
+///
+/// # Example
+///
+/// ```
+/// use rxrust::prelude::*;
+///
+/// let mut a = Subject::new();
+/// let mut b = Subject::new();
+/// observable::combine_latest_all(vec![a.clone(), b.clone()])
+///   .subscribe(|v| println!("{:?}", v));
+///
+/// a.next(1);
+/// b.next(2);
+/// a.next(3);
+///
+/// // print log:
+/// // [1, 2]
+/// // [3, 2]
+/// ```
+pub fn combine_latest_all<I, S>(sources: I) -> CombineLatestAllOp<S>
+where
+  I: IntoIterator<Item = S>,
+  S: Observable,
+{
+  CombineLatestAllOp {
+    sources: sources.into_iter().collect(),
+  }
+}
+
+/// An Observable that combines the latest items of a list of Observables.
+///
+/// This struct is created by the [`combine_latest_all`] function. See its
+/// documentation for more.
+#[derive(Clone)]
+pub struct CombineLatestAllOp<S> {
+  pub(crate) sources: Vec<S>,
+}
+
+impl<S: Observable> Observable for CombineLatestAllOp<S> {
+  type Item = Vec<S::Item>;
+  type Err = S::Err;
+}
+
+#[doc(hidden)]
+macro observable_impl(
+  $subscription:ty, $sharer:path, $mutability_enabler:path,
+  $($marker:ident +)* $lf: lifetime
+) {
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + $($marker +)* $lf>(
+    self,
+    subscriber: Subscriber<O, $subscription>,
+  ) -> Self::Unsub {
+    let mut sub = subscriber.subscription;
+    let mut o_combine = CombineLatestAllObserver {
+      observer: subscriber.observer,
+      subscription: sub.clone(),
+      values: self.sources.iter().map(|_| None).collect(),
+      completed: 0,
+    };
+    if self.sources.is_empty() {
+      o_combine.complete();
+      return sub;
+    }
+    let o_combine = $sharer($mutability_enabler(o_combine));
+    for (index, source) in self.sources.into_iter().enumerate() {
+      sub.add(source.actual_subscribe(Subscriber {
+        observer: IndexedObserver(o_combine.clone(), index),
+        subscription: <$subscription>::default(),
+      }));
+    }
+    sub
+  }
+}
+
+impl<'a, S> LocalObservable<'a> for CombineLatestAllOp<S>
+where
+  S: LocalObservable<'a>,
+  S::Item: Clone + 'a,
+{
+  type Unsub = LocalSubscription;
+  observable_impl!(LocalSubscription, Rc::new, RefCell::new, 'a);
+}
+
+impl<S> SharedObservable for CombineLatestAllOp<S>
+where
+  S: SharedObservable,
+  S::Item: Clone + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  observable_impl!(
+    SharedSubscription,
+    Arc::new,
+    Mutex::new,
+    Send + Sync + 'static
+  );
+}
+
+struct CombineLatestAllObserver<O, U, Item> {
+  observer: O,
+  subscription: U,
+  values: Vec<Option<Item>>,
+  completed: usize,
+}
+
+impl<O, U, Item, Err> Observer<(usize, Item), Err>
+  for CombineLatestAllObserver<O, U, Item>
+where
+  O: Observer<Vec<Item>, Err>,
+  U: SubscriptionLike,
+  Item: Clone,
+{
+  fn next(&mut self, (index, value): (usize, Item)) {
+    self.values[index] = Some(value);
+    let latest: Option<Vec<_>> = self.values.iter().cloned().collect();
+    if let Some(latest) = latest {
+      self.observer.next(latest);
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    self.observer.error(err);
+    self.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    self.completed += 1;
+    if self.completed >= self.values.len() {
+      self.subscription.unsubscribe();
+      self.observer.complete();
+    }
+  }
+}
+
+struct IndexedObserver<O>(O, usize);
+
+impl<O, Item, Err> Observer<Item, Err> for IndexedObserver<O>
+where
+  O: Observer<(usize, Item), Err>,
+{
+  fn next(&mut self, value: Item) { self.0.next((self.1, value)); }
+
+  error_proxy_impl!(Err, 0);
+  complete_proxy_impl!(0);
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn smoke() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      a.clone()
+        .combine_latest(b.clone(), |a, b| (a, b))
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      a.next(1);
+      a.next(2);
+      b.next('a');
+      a.next(3);
+      b.next('b');
+      a.complete();
+      b.next('c');
+      b.complete();
+    }
+    assert_eq!(values, vec![(2, 'a'), (3, 'a'), (3, 'b'), (3, 'c')]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn complete_all() {
+    let mut completed = false;
+    {
+      let mut a = Subject::new();
+      a.clone()
+        .combine_latest(Subject::new(), |a: (), b: ()| (a, b))
+        .subscribe_complete(|_| {}, || completed = true);
+
+      a.complete();
+    }
+    assert!(!completed);
+  }
+
+  #[test]
+  fn error() {
+    let mut errors = 0;
+    {
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      a.clone()
+        .combine_latest(b.clone(), |a: i32, b: i32| a + b)
+        .subscribe_err(|_| {}, |_| errors += 1);
+
+      a.error("");
+      b.error("");
+    }
+    assert_eq!(errors, 1);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut a = SharedSubject::new();
+    let mut b = SharedSubject::new();
+    a.clone()
+      .combine_latest(b.clone(), |a: i32, b: i32| a + b)
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    a.next(1);
+    b.next(10);
+    a.next(2);
+    assert_eq!(*values.lock().unwrap(), vec![11, 12]);
+  }
+
+  #[test]
+  fn all() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      let mut c = Subject::new();
+      observable::combine_latest_all(vec![a.clone(), b.clone(), c.clone()])
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      a.next(1);
+      b.next(2);
+      c.next(3);
+      b.next(4);
+      a.complete();
+      b.complete();
+      c.next(5);
+      c.complete();
+    }
+    assert_eq!(values, vec![vec![1, 2, 3], vec![1, 4, 3], vec![1, 4, 5]]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn all_empty() {
+    let mut completed = false;
+    observable::combine_latest_all(Vec::<Subject<_, _>>::new())
+      .subscribe_complete(|_: Vec<i32>| {}, || completed = true);
+    assert!(completed);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let c = observable::of(1).combine_latest(observable::of(2), |a, b| a + b);
+    c.clone()
+      .combine_latest(c.clone(), |a, b| a + b)
+      .subscribe(|_| {});
+    c.clone()
+      .combine_latest(c, |a, b| a + b)
+      .to_shared()
+      .subscribe(|_| {});
+
+    let c = observable::combine_latest_all(vec![observable::of(1)]);
+    c.clone().subscribe(|_| {});
+    c.to_shared().subscribe(|_| {});
+  }
+}
@@ -0,0 +1,196 @@
+use crate::prelude::*;
+
+/// An Observable that emits all items of the first observable, and then all
+/// items of the second one.
+///
+/// This struct is created by the concat method on
+/// [Observable](Observable::concat). See its documentation for more.
+#[derive(Clone)]
+pub struct ConcatOp<S1, S2> {
+  pub(crate) source1: S1,
+  pub(crate) source2: S2,
+}
+
+impl<S1, S2> Observable for ConcatOp<S1, S2>
+where
+  S1: Observable,
+  S2: Observable<Item = S1::Item, Err = S1::Err>,
+{
+  type Item = S1::Item;
+  type Err = S1::Err;
+}
+
+#[doc(hidden)]
+macro observable_impl($subscription:ty, $($marker:ident +)* $lf: lifetime) {
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + $($marker +)* $lf>(
+    self,
+    subscriber: Subscriber<O, $subscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    subscription.add(self.source1.actual_subscribe(Subscriber {
+      observer: ConcatObserver {
+        observer: Some(subscriber.observer),
+        source: Some(self.source2),
+        subscription: subscription.clone(),
+      },
+      subscription: <$subscription>::default(),
+    }));
+    subscription
+  }
+}
+
+impl<'a, S1, S2> LocalObservable<'a> for ConcatOp<S1, S2>
+where
+  S1: LocalObservable<'a>,
+  S2: LocalObservable<'a, Item = S1::Item, Err = S1::Err> + 'a,
+{
+  type Unsub = LocalSubscription;
+  observable_impl!(LocalSubscription, 'a);
+}
+
+impl<S1, S2> SharedObservable for ConcatOp<S1, S2>
+where
+  S1: SharedObservable,
+  S2: SharedObservable<Item = S1::Item, Err = S1::Err> + Send + Sync + 'static,
+  S1::Unsub: Send + Sync,
+  S2::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  observable_impl!(SharedSubscription, Send + Sync + 'static);
+}
+
+pub struct ConcatObserver<O, S, U> {
+  // the observer is handed over to the second observable once the first
+  // completes.
+  observer: Option<O>,
+  source: Option<S>,
+  subscription: U,
+}
+
+#[doc(hidden)]
+macro observer_impl($item: ident, $err: ident, $subscription: ty) {
+  fn next(&mut self, value: $item) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    if let Some(mut observer) = self.observer.take() {
+      observer.error(err);
+      self.subscription.unsubscribe();
+    }
+  }
+
+  fn complete(&mut self) {
+    if let (Some(observer), Some(source)) =
+      (self.observer.take(), self.source.take())
+    {
+      self.subscription.add(source.actual_subscribe(Subscriber {
+        observer,
+        subscription: <$subscription>::default(),
+      }));
+    }
+  }
+}
+
+impl<'a, Item, Err, O, S> Observer<Item, Err>
+  for ConcatObserver<O, S, LocalSubscription>
+where
+  O: Observer<Item, Err> + 'a,
+  S: LocalObservable<'a, Item = Item, Err = Err>,
+{
+  observer_impl!(Item, Err, LocalSubscription);
+}
+
+impl<Item, Err, O, S> Observer<Item, Err>
+  for ConcatObserver<O, S, SharedSubscription>
+where
+  O: Observer<Item, Err> + Send + Sync + 'static,
+  S: SharedObservable<Item = Item, Err = Err>,
+  S::Unsub: Send + Sync,
+{
+  observer_impl!(Item, Err, SharedSubscription);
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn smoke() {
+    let mut values = vec![];
+    let mut completed = 0;
+    observable::from_iter(0..3)
+      .concat(observable::from_iter(3..6))
+      .subscribe_complete(|v| values.push(v), || completed += 1);
+
+    assert_eq!(values, (0..6).collect::<Vec<_>>());
+    assert_eq!(completed, 1);
+  }
+
+  #[test]
+  fn wait_first_complete() {
+    let mut values = vec![];
+    {
+      let mut first = Subject::new();
+      let mut second = Subject::new();
+      first
+        .clone()
+        .concat(second.clone())
+        .subscribe(|v| values.push(v));
+
+      // `second` is not subscribed until `first` completes.
+      second.next(0);
+      first.next(1);
+      first.complete();
+      second.next(2);
+    }
+    assert_eq!(values, vec![1, 2]);
+  }
+
+  #[test]
+  fn error_short_circuit() {
+    let mut errors = 0;
+    let mut values = vec![];
+    observable::of_result(Err("error"))
+      .concat(observable::of_result(Ok(1)))
+      .subscribe_err(|v| values.push(v), |_| errors += 1);
+
+    assert_eq!(errors, 1);
+    assert!(values.is_empty());
+  }
+
+  #[test]
+  fn unsubscribe() {
+    let mut first = Subject::new();
+    let mut second = Subject::new();
+    let mut subscription = first
+      .clone()
+      .concat(second.clone())
+      .subscribe(|_: i32| unreachable!("unsubscribe not work."));
+    first.complete();
+    subscription.unsubscribe();
+    second.next(1);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    observable::from_iter(0..3)
+      .concat(observable::from_iter(3..6))
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    assert_eq!(*values.lock().unwrap(), (0..6).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let c = observable::of(1).concat(observable::of(2));
+    c.clone().concat(c.clone()).subscribe(|_| {});
+    c.clone().concat(c).to_shared().subscribe(|_| {});
+  }
+}
@@ -3,17 +3,19 @@ use observable::observable_proxy_impl;
 use std::time::Duration;
 
 #[derive(Clone)]
-pub struct DelayOp<S> {
+pub struct DelayOp<S, SD = Schedulers> {
   pub(crate) source: S,
   pub(crate) delay: Duration,
+  pub(crate) scheduler: SD,
 }
 
-observable_proxy_impl!(DelayOp, S);
+observable_proxy_impl!(DelayOp, S, SD);
 
-impl<S> SharedObservable for DelayOp<S>
+impl<S, SD> SharedObservable for DelayOp<S, SD>
 where
   S: SharedObservable + Send + Sync + 'static,
   S::Unsub: Send + Sync,
+  SD: Scheduler,
 {
   type Unsub = SharedSubscription;
   fn actual_subscribe<
@@ -22,9 +24,39 @@ where
     self,
     subscriber: Subscriber<O, SharedSubscription>,
   ) -> Self::Unsub {
-    let Self { delay, source } = self;
+    let Self {
+      delay,
+      source,
+      scheduler,
+    } = self;
 
-    Schedulers::ThreadPool.schedule(
+    scheduler.schedule(
+      move |mut subscription, _| {
+        subscription.add(source.actual_subscribe(subscriber));
+      },
+      Some(delay),
+      (),
+    )
+  }
+}
+
+impl<S, SD> LocalObservable<'static> for DelayOp<S, SD>
+where
+  S: LocalObservable<'static> + 'static,
+  SD: LocalScheduler,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let Self {
+      delay,
+      source,
+      scheduler,
+    } = self;
+
+    scheduler.schedule(
       move |mut subscription, _| {
         subscription.add(source.actual_subscribe(subscriber));
       },
@@ -49,3 +81,36 @@ fn smoke() {
   std::thread::sleep(Duration::from_millis(60));
   assert_eq!(*c_value.lock().unwrap(), 1);
 }
+
+#[test]
+fn virtual_time() {
+  use std::sync::{Arc, Mutex};
+  let scheduler = TestScheduler::new();
+  let value = Arc::new(Mutex::new(0));
+  let c_value = value.clone();
+  observable::of(1)
+    .delay_on(Duration::from_millis(50), scheduler.clone())
+    .to_shared()
+    .subscribe(move |v| {
+      *value.lock().unwrap() = v;
+    });
+  scheduler.advance_by(Duration::from_millis(49));
+  assert_eq!(*c_value.lock().unwrap(), 0);
+  scheduler.advance_by(Duration::from_millis(1));
+  assert_eq!(*c_value.lock().unwrap(), 1);
+}
+
+#[test]
+fn local() {
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  let scheduler = LocalPoolScheduler::new();
+  let value = Rc::new(RefCell::new(Rc::new(0)));
+  let c_value = value.clone();
+  observable::of(Rc::new(1))
+    .delay_on(Duration::from_millis(10), scheduler.clone())
+    .subscribe(move |v| *c_value.borrow_mut() = v);
+  assert_eq!(**value.borrow(), 0);
+  scheduler.run();
+  assert_eq!(**value.borrow(), 1);
+}
@@ -0,0 +1,512 @@
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// An Observable that maps every item of the source to an inner observable
+/// and merges the emissions of all the inner observables.
+///
+/// This struct is created by the flat_map method on
+/// [Observable](Observable::flat_map). See its documentation for more.
+#[derive(Clone)]
+pub struct FlatMapOp<S, M> {
+  pub(crate) source: S,
+  pub(crate) func: M,
+  pub(crate) concurrent: usize,
+}
+
+impl<S, M, Inner> Observable for FlatMapOp<S, M>
+where
+  S: Observable,
+  M: FnMut(S::Item) -> Inner,
+  Inner: Observable<Err = S::Err>,
+{
+  type Item = Inner::Item;
+  type Err = S::Err;
+}
+
+impl<'a, S, M, Inner> LocalObservable<'a> for FlatMapOp<S, M>
+where
+  S: LocalObservable<'a>,
+  S::Item: 'a,
+  M: FnMut(S::Item) -> Inner + 'a,
+  Inner: LocalObservable<'a, Err = S::Err>,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let state = Rc::new(RefCell::new(FlatMapState::new(
+      Rc::new(RefCell::new(subscriber.observer)),
+      self.func,
+      subscription.clone(),
+      self.concurrent,
+    )));
+    subscription.add(self.source.actual_subscribe(Subscriber {
+      observer: FlatMapOuterObserver(state),
+      subscription: LocalSubscription::default(),
+    }));
+    subscription
+  }
+}
+
+impl<S, M, Inner> SharedObservable for FlatMapOp<S, M>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Unsub: Send + Sync,
+  M: FnMut(S::Item) -> Inner + Send + 'static,
+  Inner: SharedObservable<Err = S::Err>,
+  Inner::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let state = Arc::new(Mutex::new(FlatMapState::new(
+      Arc::new(Mutex::new(subscriber.observer)),
+      self.func,
+      subscription.clone(),
+      self.concurrent,
+    )));
+    subscription.add(self.source.actual_subscribe(Subscriber {
+      observer: FlatMapOuterObserver(state),
+      subscription: SharedSubscription::default(),
+    }));
+    subscription
+  }
+}
+
+struct FlatMapState<O, M, Item, U> {
+  // shared by the inner observers, which emit without holding the state: the
+  // downstream may feed the source again.
+  observer: O,
+  func: M,
+  // the downstream subscription, every live inner subscription is added to it.
+  subscription: U,
+  concurrent: usize,
+  active: usize,
+  // outer items waiting for a free slot to subscribe their inner observable.
+  buffer: VecDeque<Item>,
+  source_completed: bool,
+}
+
+impl<O, M, Item, U> FlatMapState<O, M, Item, U> {
+  fn new(observer: O, func: M, subscription: U, concurrent: usize) -> Self {
+    FlatMapState {
+      observer,
+      func,
+      subscription,
+      concurrent,
+      active: 0,
+      buffer: VecDeque::new(),
+      source_completed: false,
+    }
+  }
+
+  /// Map `value` to an inner observable if there is a free slot, otherwise
+  /// queue it until an active inner observable completes.
+  fn accept<Inner>(&mut self, value: Item) -> Option<Inner>
+  where
+    M: FnMut(Item) -> Inner,
+  {
+    if self.active < self.concurrent {
+      self.active += 1;
+      Some((self.func)(value))
+    } else {
+      self.buffer.push_back(value);
+      None
+    }
+  }
+
+  /// Release the slot of a completed inner observable, and take the next
+  /// queued item if there is one.
+  fn release<Inner>(&mut self) -> Option<Inner>
+  where
+    M: FnMut(Item) -> Inner,
+  {
+    self.active -= 1;
+    let value = self.buffer.pop_front()?;
+    self.active += 1;
+    Some((self.func)(value))
+  }
+
+  #[inline]
+  fn is_done(&self) -> bool {
+    self.source_completed && self.active == 0 && self.buffer.is_empty()
+  }
+}
+
+type LocalState<O, M, Item> =
+  Rc<RefCell<FlatMapState<Rc<RefCell<O>>, M, Item, LocalSubscription>>>;
+type SharedState<O, M, Item> =
+  Arc<Mutex<FlatMapState<Arc<Mutex<O>>, M, Item, SharedSubscription>>>;
+
+pub struct FlatMapOuterObserver<S>(S);
+
+pub struct FlatMapInnerObserver<S, U> {
+  state: S,
+  // the subscription of this inner observable, removed from the downstream
+  // subscription once the inner completes.
+  subscription: U,
+}
+
+fn subscribe_local_inner<'a, O, M, Item, Inner>(
+  state: &LocalState<O, M, Item>,
+  inner: Inner,
+) where
+  O: Observer<Inner::Item, Inner::Err> + 'a,
+  M: FnMut(Item) -> Inner + 'a,
+  Item: 'a,
+  Inner: LocalObservable<'a>,
+{
+  let mut subscription = LocalSubscription::default();
+  state.borrow_mut().subscription.add(subscription.clone());
+  let unsub = inner.actual_subscribe(Subscriber {
+    observer: FlatMapInnerObserver {
+      state: state.clone(),
+      subscription: subscription.clone(),
+    },
+    subscription: subscription.clone(),
+  });
+  subscription.add(unsub);
+}
+
+fn subscribe_shared_inner<O, M, Item, Inner>(
+  state: &SharedState<O, M, Item>,
+  inner: Inner,
+) where
+  O: Observer<Inner::Item, Inner::Err> + Send + Sync + 'static,
+  M: FnMut(Item) -> Inner + Send + 'static,
+  Item: Send + 'static,
+  Inner: SharedObservable,
+  Inner::Unsub: Send + Sync,
+{
+  let mut subscription = SharedSubscription::default();
+  state.lock().unwrap().subscription.add(subscription.clone());
+  let unsub = inner.actual_subscribe(Subscriber {
+    observer: FlatMapInnerObserver {
+      state: state.clone(),
+      subscription: subscription.clone(),
+    },
+    subscription: subscription.clone(),
+  });
+  subscription.add(unsub);
+}
+
+#[doc(hidden)]
+macro outer_observer_impl(
+  $item: ident, $err: ident, $subscribe: ident,
+  $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    // don't hold the state while subscribing, the inner observable may emit
+    // synchronously.
+    let inner = self.0.$($lock$($parentheses)?).+.accept(value);
+    if let Some(inner) = inner {
+      $subscribe(&self.0, inner);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    state.observer.$($lock$($parentheses)?).+.error(err);
+    state.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    state.source_completed = true;
+    if state.is_done() {
+      state.observer.$($lock$($parentheses)?).+.complete();
+      state.subscription.unsubscribe();
+    }
+  }
+}
+
+#[doc(hidden)]
+macro inner_observer_impl(
+  $item: ident, $err: ident, $subscribe: ident,
+  $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    let observer = self.state.$($lock$($parentheses)?).+.observer.clone();
+    observer.$($lock$($parentheses)?).+.next(value);
+  }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    state.observer.$($lock$($parentheses)?).+.error(err);
+    state.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    let inner = {
+      let mut state = self.state.$($lock$($parentheses)?).+;
+      state.subscription.remove(&self.subscription);
+      let inner = state.release();
+      if state.is_done() {
+        state.observer.$($lock$($parentheses)?).+.complete();
+        state.subscription.unsubscribe();
+      }
+      inner
+    };
+    if let Some(inner) = inner {
+      $subscribe(&self.state, inner);
+    }
+  }
+}
+
+impl<'a, Item, Err, O, M, Inner> Observer<Item, Err>
+  for FlatMapOuterObserver<LocalState<O, M, Item>>
+where
+  O: Observer<Inner::Item, Err> + 'a,
+  M: FnMut(Item) -> Inner + 'a,
+  Item: 'a,
+  Inner: LocalObservable<'a, Err = Err>,
+{
+  outer_observer_impl!(Item, Err, subscribe_local_inner, borrow_mut());
+}
+
+impl<Item, Err, O, M, Inner> Observer<Item, Err>
+  for FlatMapOuterObserver<SharedState<O, M, Item>>
+where
+  O: Observer<Inner::Item, Err> + Send + Sync + 'static,
+  M: FnMut(Item) -> Inner + Send + 'static,
+  Item: Send + 'static,
+  Inner: SharedObservable<Err = Err>,
+  Inner::Unsub: Send + Sync,
+{
+  outer_observer_impl!(Item, Err, subscribe_shared_inner, lock().unwrap());
+}
+
+impl<'a, Item, InnerItem, Err, O, M, Inner> Observer<InnerItem, Err>
+  for FlatMapInnerObserver<LocalState<O, M, Item>, LocalSubscription>
+where
+  O: Observer<InnerItem, Err> + 'a,
+  M: FnMut(Item) -> Inner + 'a,
+  Item: 'a,
+  Inner: LocalObservable<'a, Item = InnerItem, Err = Err>,
+{
+  inner_observer_impl!(InnerItem, Err, subscribe_local_inner, borrow_mut());
+}
+
+impl<Item, InnerItem, Err, O, M, Inner> Observer<InnerItem, Err>
+  for FlatMapInnerObserver<SharedState<O, M, Item>, SharedSubscription>
+where
+  O: Observer<InnerItem, Err> + Send + Sync + 'static,
+  M: FnMut(Item) -> Inner + Send + 'static,
+  Item: Send + 'static,
+  Inner: SharedObservable<Item = InnerItem, Err = Err>,
+  Inner::Unsub: Send + Sync,
+{
+  inner_observer_impl!(InnerItem, Err, subscribe_shared_inner, lock().unwrap());
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn smoke() {
+    let mut values = vec![];
+    let mut completed = false;
+    observable::from_iter(0..3)
+      .flat_map(|v| observable::from_iter(vec![v; 2]))
+      .subscribe_complete(|v| values.push(v), || completed = true);
+
+    assert_eq!(values, vec![0, 0, 1, 1, 2, 2]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn merge_inner_emissions() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut source = Subject::new();
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      let inners = [a.clone(), b.clone()];
+      source
+        .clone()
+        .flat_map(move |i: usize| inners[i].clone())
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      source.next(0);
+      source.next(1);
+      a.next(1);
+      b.next(2);
+      a.next(3);
+      source.complete();
+      a.complete();
+      b.next(4);
+      b.complete();
+    }
+    assert_eq!(values, vec![1, 2, 3, 4]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn concurrent_limit() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut source = Subject::new();
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      let inners = [a.clone(), b.clone()];
+      source
+        .clone()
+        .flat_map_concurrent(move |i: usize| inners[i].clone(), 1)
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      source.next(0);
+      source.next(1);
+      // `b` is queued until `a` completes
+      b.next(0);
+      a.next(1);
+      source.complete();
+      a.complete();
+      b.next(2);
+      b.complete();
+    }
+    assert_eq!(values, vec![1, 2]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn concat_map() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut source = Subject::new();
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      let inners = [a.clone(), b.clone()];
+      source
+        .clone()
+        .concat_map(move |i: usize| inners[i].clone())
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      source.next(1);
+      source.next(0);
+      a.next(0);
+      b.next(1);
+      b.complete();
+      a.next(2);
+      source.complete();
+      a.complete();
+    }
+    assert_eq!(values, vec![1, 2]);
+    assert!(completed);
+  }
+
+  #[test]
+  #[should_panic]
+  fn zero_concurrent() {
+    observable::of(1).flat_map_concurrent(observable::of, 0);
+  }
+
+  #[test]
+  fn unsubscribe_inner() {
+    let mut source = Subject::new();
+    let inner = Subject::new();
+    let c_inner = inner.clone();
+    source
+      .clone()
+      .flat_map(move |_: ()| c_inner.clone())
+      .subscribe(|_: i32| unreachable!("unsubscribe not work."))
+      .unsubscribe();
+    source.next(());
+    assert_eq!(inner.subscribed_size(), 0);
+
+    let mut subscription = source
+      .clone()
+      .flat_map({
+        let inner = inner.clone();
+        move |_: ()| inner.clone()
+      })
+      .subscribe(|_: i32| {});
+    source.next(());
+    source.next(());
+    assert_eq!(inner.subscribed_size(), 2);
+    subscription.unsubscribe();
+    assert!(inner.observers.borrow().list.iter().all(|o| o.is_closed()));
+  }
+
+  #[test]
+  fn error() {
+    let mut errors = 0;
+    let mut completed = 0;
+    {
+      let mut source = Subject::new();
+      let mut inner = Subject::new();
+      let c_inner = inner.clone();
+      source
+        .clone()
+        .flat_map(move |_: ()| c_inner.clone())
+        .subscribe_all(|_: i32| {}, |_| errors += 1, || completed += 1);
+      source.next(());
+      inner.error("");
+      source.complete();
+    }
+    assert_eq!(errors, 1);
+    assert_eq!(completed, 0);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    observable::from_iter(0..3)
+      .flat_map(observable::of)
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    assert_eq!(*values.lock().unwrap(), vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn feed_source_from_downstream() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut source: SharedSubject<i32, ()> = Subject::new();
+    let mut c_source = source.clone();
+    let c_scheduler = scheduler.clone();
+    source
+      .clone()
+      .flat_map(move |v| {
+        let delay = Duration::from_millis(1);
+        observable::of(v).delay_on(delay, c_scheduler.clone())
+      })
+      .to_shared()
+      .subscribe(move |v| {
+        c_values.lock().unwrap().push(v);
+        if v < 3 {
+          c_source.next(v + 1);
+        }
+      });
+
+    source.next(0);
+    scheduler.advance_by(Duration::from_millis(10));
+    assert_eq!(*values.lock().unwrap(), vec![0, 1, 2, 3]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let m = observable::from_iter(0..10).flat_map(observable::of);
+    m.clone().flat_map(observable::of).subscribe(|_| {});
+    m.flat_map(observable::of).to_shared().subscribe(|_| {});
+  }
+}
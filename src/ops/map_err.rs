@@ -0,0 +1,139 @@
+use crate::observer::{complete_proxy_impl, next_proxy_impl};
+use crate::prelude::*;
+
+#[derive(Clone)]
+pub struct MapErrOp<S, M> {
+  pub(crate) source: S,
+  pub(crate) func: M,
+}
+
+#[doc(hidden)]
+macro observable_impl($subscription:ty, $($marker:ident +)* $lf: lifetime) {
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + $($marker +)* $lf>(
+    self,
+    subscriber: Subscriber<O, $subscription>,
+  ) -> Self::Unsub {
+    let map = self.func;
+    self.source.actual_subscribe(Subscriber {
+      observer: MapErrObserver {
+        observer: subscriber.observer,
+        map: Some(map),
+      },
+      subscription: subscriber.subscription,
+    })
+  }
+}
+
+impl<Err, S, M> Observable for MapErrOp<S, M>
+where
+  S: Observable,
+  M: FnOnce(S::Err) -> Err,
+{
+  type Item = S::Item;
+  type Err = Err;
+}
+
+impl<'a, Err, S, M> LocalObservable<'a> for MapErrOp<S, M>
+where
+  S: LocalObservable<'a>,
+  M: FnOnce(S::Err) -> Err + 'a,
+{
+  type Unsub = S::Unsub;
+  observable_impl!(LocalSubscription,'a);
+}
+
+impl<Err, S, M> SharedObservable for MapErrOp<S, M>
+where
+  S: SharedObservable,
+  M: FnOnce(S::Err) -> Err + Send + Sync + 'static,
+{
+  type Unsub = S::Unsub;
+  observable_impl!(SharedSubscription, Send + Sync + 'static);
+}
+
+pub struct MapErrObserver<O, M> {
+  observer: O,
+  // taken by the error, an observable emits at most one.
+  map: Option<M>,
+}
+
+impl<Item, Err, O, M, B> Observer<Item, Err> for MapErrObserver<O, M>
+where
+  O: Observer<Item, B>,
+  M: FnOnce(Err) -> B,
+{
+  next_proxy_impl!(Item, observer);
+
+  fn error(&mut self, err: Err) {
+    if let Some(map) = self.map.take() {
+      self.observer.error(map(err))
+    }
+  }
+
+  complete_proxy_impl!(observer);
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+
+  #[test]
+  fn map_error() {
+    let mut errors = vec![];
+    observable::throw(1)
+      .map_err(|e| e.to_string())
+      .subscribe_err(|_| {}, |e| errors.push(e));
+    assert_eq!(errors, vec!["1".to_string()]);
+  }
+
+  #[test]
+  fn items_and_complete_pass_through() {
+    let mut values = vec![];
+    let mut completed = false;
+    observable::from_iter(0..3)
+      .map_err(|_: ()| "never")
+      .subscribe_all(|v| values.push(v), |_| {}, || completed = true);
+    assert_eq!(values, vec![0, 1, 2]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn err_into_merge() {
+    #[derive(Debug, PartialEq)]
+    enum Error {
+      Io(&'static str),
+      Unit,
+    }
+    impl From<&'static str> for Error {
+      fn from(e: &'static str) -> Self { Error::Io(e) }
+    }
+    impl From<()> for Error {
+      fn from(_: ()) -> Self { Error::Unit }
+    }
+
+    let mut errors = vec![];
+    observable::throw("io")
+      .err_into::<Error>()
+      .merge(observable::empty().err_into())
+      .subscribe_err(|_| {}, |e| errors.push(e));
+    assert_eq!(errors, vec![Error::Io("io")]);
+
+    let mut errors = vec![];
+    observable::throw(())
+      .err_into::<Error>()
+      .merge(observable::empty().err_into())
+      .subscribe_err(|_| {}, |e| errors.push(e));
+    assert_eq!(errors, vec![Error::Unit]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let m = observable::throw(1).map_err(|e| e + 1);
+    m.clone().map_err(|e| e * 2).to_shared().subscribe_err(|_| {}, |_| {});
+    m.to_shared().subscribe_err(|_| {}, |_| {});
+
+    let i = observable::throw(1u8).err_into::<u32>();
+    i.clone().err_into::<u64>().to_shared().subscribe_err(|_| {}, |_| {});
+    i.to_shared().subscribe_err(|_| {}, |_| {});
+  }
+}
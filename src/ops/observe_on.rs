@@ -1,7 +1,9 @@
 use crate::prelude::*;
 use crate::scheduler::Scheduler;
 use observable::observable_proxy_impl;
+use std::cell::RefCell;
 use std::marker::PhantomData;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
@@ -73,6 +75,38 @@ where
   }
 }
 
+impl<S, SD> LocalObservable<'static> for ObserveOnOp<'static, S, SD>
+where
+  S: LocalObservable<'static>,
+  S::Item: 'static,
+  S::Err: 'static,
+  SD: LocalScheduler + 'static,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let observer = LocalObserveOnObserver {
+      observer: Rc::new(RefCell::new(subscriber.observer)),
+      proxy: subscription.clone(),
+      scheduler: self.scheduler,
+    };
+    // the source closes its own subscription once it's done, the scheduled
+    // notifications are still pending on the downstream one, which is
+    // returned so unsubscribing it cancels them.
+    let mut source = LocalSubscription::default();
+    subscription.add(source.clone());
+    let unsub = self.source.actual_subscribe(Subscriber {
+      observer,
+      subscription: source.clone(),
+    });
+    source.add(unsub);
+    subscription
+  }
+}
+
 pub struct ObserveOnObserver<O, SD, U> {
   observer: Arc<Mutex<O>>,
   proxy: U,
@@ -146,6 +180,55 @@ where
   impl_observer!(Item, Err);
 }
 
+pub struct LocalObserveOnObserver<O, SD> {
+  observer: Rc<RefCell<O>>,
+  proxy: LocalSubscription,
+  scheduler: SD,
+}
+
+impl<Item, Err, O, SD> Observer<Item, Err> for LocalObserveOnObserver<O, SD>
+where
+  Item: 'static,
+  Err: 'static,
+  O: Observer<Item, Err> + 'static,
+  SD: LocalScheduler,
+{
+  fn next(&mut self, value: Item) {
+    let s = self.scheduler.schedule(
+      |_, (v, observer): (Item, Rc<RefCell<O>>)| observer.borrow_mut().next(v),
+      None,
+      (value, self.observer.clone()),
+    );
+    self.proxy.add(s);
+  }
+
+  fn error(&mut self, err: Err) {
+    let mut proxy = self.proxy.clone();
+    let s = self.scheduler.schedule(
+      move |_, (e, observer): (Err, Rc<RefCell<O>>)| {
+        observer.borrow_mut().error(e);
+        proxy.unsubscribe();
+      },
+      None,
+      (err, self.observer.clone()),
+    );
+    self.proxy.add(s);
+  }
+
+  fn complete(&mut self) {
+    let mut proxy = self.proxy.clone();
+    let s = self.scheduler.schedule(
+      move |_, observer: Rc<RefCell<O>>| {
+        observer.borrow_mut().complete();
+        proxy.unsubscribe();
+      },
+      None,
+      self.observer.clone(),
+    );
+    self.proxy.add(s);
+  }
+}
+
 #[cfg(test)]
 mod test {
   use crate::prelude::*;
@@ -189,6 +272,50 @@ mod test {
   #[test]
   fn new_thread_unsubscribe() { unsubscribe_scheduler(Schedulers::NewThread) }
 
+  #[test]
+  fn local() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let scheduler = LocalPoolScheduler::new();
+    let emitted = Rc::new(RefCell::new(vec![]));
+    let c_emitted = emitted.clone();
+    let completed = Rc::new(RefCell::new(false));
+    let c_completed = completed.clone();
+    observable::from_iter((0..3).map(Rc::new))
+      .observe_on(scheduler.clone())
+      .subscribe_complete(
+        move |v| emitted.borrow_mut().push(*v),
+        move || *c_completed.borrow_mut() = true,
+      );
+
+    assert!(c_emitted.borrow().is_empty());
+    scheduler.run();
+    assert_eq!(*c_emitted.borrow(), vec![0, 1, 2]);
+    assert!(*completed.borrow());
+  }
+
+  #[test]
+  fn local_unsubscribe() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let scheduler = LocalPoolScheduler::new();
+    let emitted = Rc::new(RefCell::new(vec![]));
+    let c_emitted = emitted.clone();
+    let completed = Rc::new(RefCell::new(false));
+    let c_completed = completed.clone();
+    observable::from_iter((0..3).map(Rc::new))
+      .observe_on(scheduler.clone())
+      .subscribe_complete(
+        move |v| emitted.borrow_mut().push(*v),
+        move || *c_completed.borrow_mut() = true,
+      )
+      .unsubscribe();
+
+    scheduler.run();
+    assert!(c_emitted.borrow().is_empty());
+    assert!(!*completed.borrow());
+  }
+
   // #[test]
   // fn sync_unsubscribe() { unsubscribe_scheduler(Schedulers::Sync) }
 
@@ -0,0 +1,603 @@
+use crate::prelude::*;
+use observable::observable_proxy_impl;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// An Observable that mirrors the source, and resubscribes to it when it
+/// emits an error, at most `count` times.
+///
+/// This struct is created by the retry method on
+/// [Observable](Observable::retry). See its documentation for more.
+#[derive(Clone)]
+pub struct RetryOp<S> {
+  pub(crate) source: S,
+  pub(crate) count: usize,
+}
+
+observable_proxy_impl!(RetryOp, S);
+
+/// Subscribes an attempt of `source`, which can still be retried `remaining`
+/// times. The attempt has its own subscription, removed from the downstream
+/// one once the attempt fails.
+#[doc(hidden)]
+macro subscribe_attempt(
+  $subscription: ty,
+  $source: expr,
+  $remaining: expr,
+  $observer: expr,
+  $downstream: expr
+) {{
+  let mut downstream = $downstream;
+  let mut attempt = <$subscription>::default();
+  downstream.add(attempt.clone());
+  let source = $source;
+  let unsub = source.clone().actual_subscribe(Subscriber {
+    observer: RetryObserver {
+      observer: Some($observer),
+      source: Some(source),
+      remaining: $remaining,
+      attempt: attempt.clone(),
+      subscription: downstream,
+    },
+    subscription: attempt.clone(),
+  });
+  attempt.add(unsub);
+}}
+
+impl<'a, S> LocalObservable<'a> for RetryOp<S>
+where
+  S: LocalObservable<'a> + Clone + 'a,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let subscription = subscriber.subscription;
+    subscribe_attempt!(
+      LocalSubscription,
+      self.source,
+      self.count,
+      subscriber.observer,
+      subscription.clone()
+    );
+    subscription
+  }
+}
+
+impl<S> SharedObservable for RetryOp<S>
+where
+  S: SharedObservable + Clone + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let subscription = subscriber.subscription;
+    subscribe_attempt!(
+      SharedSubscription,
+      self.source,
+      self.count,
+      subscriber.observer,
+      subscription.clone()
+    );
+    subscription
+  }
+}
+
+pub struct RetryObserver<O, S, U> {
+  // the observer and the source are handed over to the next attempt once this
+  // one fails.
+  observer: Option<O>,
+  source: Option<S>,
+  remaining: usize,
+  attempt: U,
+  subscription: U,
+}
+
+#[doc(hidden)]
+macro observer_impl($item: ident, $err: ident, $subscription: ty) {
+  fn next(&mut self, value: $item) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    self.attempt.unsubscribe();
+    self.subscription.remove(&self.attempt);
+    if let (Some(mut observer), Some(source)) =
+      (self.observer.take(), self.source.take())
+    {
+      if self.remaining == 0 {
+        observer.error(err);
+        self.subscription.unsubscribe();
+      } else {
+        subscribe_attempt!(
+          $subscription,
+          source,
+          self.remaining - 1,
+          observer,
+          self.subscription.clone()
+        );
+      }
+    }
+  }
+
+  fn complete(&mut self) {
+    if let Some(mut observer) = self.observer.take() {
+      observer.complete();
+      self.subscription.unsubscribe();
+    }
+  }
+}
+
+impl<'a, Item, Err, O, S> Observer<Item, Err>
+  for RetryObserver<O, S, LocalSubscription>
+where
+  O: Observer<Item, Err> + 'a,
+  S: LocalObservable<'a, Item = Item, Err = Err> + Clone + 'a,
+{
+  observer_impl!(Item, Err, LocalSubscription);
+}
+
+impl<Item, Err, O, S> Observer<Item, Err>
+  for RetryObserver<O, S, SharedSubscription>
+where
+  O: Observer<Item, Err> + Send + Sync + 'static,
+  S: SharedObservable<Item = Item, Err = Err> + Clone + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+{
+  observer_impl!(Item, Err, SharedSubscription);
+}
+
+/// An Observable that mirrors the source, and resubscribes to it each time
+/// the notifier emits. The notifier is created from the observable of the
+/// errors of the source.
+///
+/// This struct is created by the retry_when method on
+/// [Observable](Observable::retry_when). See its documentation for more.
+#[derive(Clone)]
+pub struct RetryWhenOp<S, F> {
+  pub(crate) source: S,
+  pub(crate) func: F,
+}
+
+observable_proxy_impl!(RetryWhenOp, S, F);
+
+pub struct RetryWhenState<O, S, Err, E, U> {
+  observer: Option<O>,
+  source: S,
+  // the subject of the errors the notifier is built from, dropped once done
+  // because the notifier it feeds holds the state.
+  errors: Option<E>,
+  // the errors waiting to be emitted by `errors`, a synchronous source may
+  // fail again while the previous error is still being emitted.
+  pending: VecDeque<Err>,
+  notifying: bool,
+  // the subscription of the current attempt of the source.
+  attempt: Option<U>,
+  subscription: U,
+}
+
+impl<O, S, Err, E, U> RetryWhenState<O, S, Err, E, U> {
+  fn new(observer: O, source: S, errors: E, subscription: U) -> Self {
+    RetryWhenState {
+      observer: Some(observer),
+      source,
+      errors: Some(errors),
+      pending: VecDeque::new(),
+      notifying: false,
+      attempt: None,
+      subscription,
+    }
+  }
+}
+
+type LocalState<'a, O, S, Err> = Rc<
+  RefCell<
+    RetryWhenState<O, S, Err, LocalSubject<'a, Err, Err>, LocalSubscription>,
+  >,
+>;
+type SharedState<O, S, Err> = Arc<
+  Mutex<
+    RetryWhenState<O, S, Err, SharedSubject<Err, Err>, SharedSubscription>,
+  >,
+>;
+
+impl<'a, S, F, N> LocalObservable<'a> for RetryWhenOp<S, F>
+where
+  S: LocalObservable<'a> + Clone + 'a,
+  S::Err: PayloadCopy + 'a,
+  F: FnOnce(LocalSubject<'a, S::Err, S::Err>) -> N,
+  N: LocalObservable<'a, Err = S::Err>,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let errors = Subject::new();
+    let state = Rc::new(RefCell::new(RetryWhenState::new(
+      subscriber.observer,
+      self.source,
+      errors.clone(),
+      subscription.clone(),
+    )));
+    subscription.add((self.func)(errors).actual_subscribe(Subscriber {
+      observer: RetryWhenNotifierObserver(state.clone()),
+      subscription: LocalSubscription::default(),
+    }));
+    local_resubscribe(&state);
+    subscription
+  }
+}
+
+impl<S, F, N> SharedObservable for RetryWhenOp<S, F>
+where
+  S: SharedObservable + Clone + Send + Sync + 'static,
+  S::Item: Send + Sync + 'static,
+  S::Err: PayloadCopy + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+  F: FnOnce(SharedSubject<S::Err, S::Err>) -> N,
+  N: SharedObservable<Err = S::Err>,
+  N::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let errors = Subject::new();
+    let state = Arc::new(Mutex::new(RetryWhenState::new(
+      subscriber.observer,
+      self.source,
+      errors.clone(),
+      subscription.clone(),
+    )));
+    subscription.add((self.func)(errors).actual_subscribe(Subscriber {
+      observer: RetryWhenNotifierObserver(state.clone()),
+      subscription: SharedSubscription::default(),
+    }));
+    shared_resubscribe(&state);
+    subscription
+  }
+}
+
+/// Unsubscribes the current attempt if any, and subscribes a new one unless
+/// the downstream is done.
+#[doc(hidden)]
+macro resubscribe_impl(
+  $state: ident, $subscription: ty, $($lock: tt $($parentheses: tt)?).+
+) {
+  let (source, attempt) = {
+    let mut state = $state.$($lock$($parentheses)?).+;
+    if state.observer.is_none() {
+      return;
+    }
+    if let Some(mut previous) = state.attempt.take() {
+      previous.unsubscribe();
+      state.subscription.remove(&previous);
+    }
+    let attempt = <$subscription>::default();
+    state.subscription.add(attempt.clone());
+    state.attempt = Some(attempt.clone());
+    (state.source.clone(), attempt)
+  };
+  // don't hold the state while subscribing, the source may emit
+  // synchronously.
+  let mut c_attempt = attempt.clone();
+  c_attempt.add(source.actual_subscribe(Subscriber {
+    observer: RetryWhenObserver($state.clone()),
+    subscription: attempt,
+  }));
+}
+
+fn local_resubscribe<'a, O, S>(state: &LocalState<'a, O, S, S::Err>)
+where
+  O: Observer<S::Item, S::Err> + 'a,
+  S: LocalObservable<'a> + Clone + 'a,
+  S::Err: PayloadCopy + 'a,
+{
+  resubscribe_impl!(state, LocalSubscription, borrow_mut());
+}
+
+fn shared_resubscribe<O, S>(state: &SharedState<O, S, S::Err>)
+where
+  O: Observer<S::Item, S::Err> + Send + Sync + 'static,
+  S: SharedObservable + Clone + Send + Sync + 'static,
+  S::Item: Send + Sync + 'static,
+  S::Err: PayloadCopy + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+{
+  resubscribe_impl!(state, SharedSubscription, lock().unwrap());
+}
+
+/// Observes the current attempt of the source, and forwards its errors to the
+/// notifier.
+pub struct RetryWhenObserver<St>(St);
+
+/// Observes the notifier, and resubscribes the source each time it emits.
+pub struct RetryWhenNotifierObserver<St>(St);
+
+#[doc(hidden)]
+macro source_observer_impl(
+  $item: ident, $err: ident, $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    if let Some(observer) = state.observer.as_mut() {
+      observer.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    {
+      let mut state = self.0.$($lock$($parentheses)?).+;
+      if let Some(mut attempt) = state.attempt.take() {
+        attempt.unsubscribe();
+        state.subscription.remove(&attempt);
+      }
+      state.pending.push_back(err);
+      if state.notifying {
+        // emitted by the loop below, up the stack.
+        return;
+      }
+      state.notifying = true;
+    }
+    // don't hold the state while emitting, the notifier may resubscribe
+    // synchronously.
+    while let Some((mut errors, err)) = {
+      let mut state = self.0.$($lock$($parentheses)?).+;
+      let next = match state.errors.clone() {
+        Some(errors) => state.pending.pop_front().map(|err| (errors, err)),
+        None => None,
+      };
+      if next.is_none() {
+        state.notifying = false;
+        state.pending.clear();
+      }
+      next
+    } {
+      errors.next(err);
+    }
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    if let Some(mut observer) = state.observer.take() {
+      observer.complete();
+      state.errors.take();
+      state.subscription.unsubscribe();
+    }
+  }
+}
+
+#[doc(hidden)]
+macro notifier_observer_impl(
+  $item: ident,
+  $err: ty,
+  $resubscribe: ident,
+  $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, _: $item) { $resubscribe(&self.0); }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    if let Some(mut observer) = state.observer.take() {
+      observer.error(err);
+      state.errors.take();
+      state.subscription.unsubscribe();
+    }
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    if let Some(mut observer) = state.observer.take() {
+      observer.complete();
+      state.errors.take();
+      state.subscription.unsubscribe();
+    }
+  }
+}
+
+impl<'a, Item, Err, O, S> Observer<Item, Err>
+  for RetryWhenObserver<LocalState<'a, O, S, Err>>
+where
+  O: Observer<Item, Err>,
+  Err: PayloadCopy,
+{
+  source_observer_impl!(Item, Err, borrow_mut());
+}
+
+impl<Item, Err, O, S> Observer<Item, Err>
+  for RetryWhenObserver<SharedState<O, S, Err>>
+where
+  O: Observer<Item, Err>,
+  Err: PayloadCopy,
+{
+  source_observer_impl!(Item, Err, lock().unwrap());
+}
+
+impl<'a, NItem, O, S> Observer<NItem, S::Err>
+  for RetryWhenNotifierObserver<LocalState<'a, O, S, S::Err>>
+where
+  O: Observer<S::Item, S::Err> + 'a,
+  S: LocalObservable<'a> + Clone + 'a,
+  S::Err: PayloadCopy + 'a,
+{
+  notifier_observer_impl!(NItem, S::Err, local_resubscribe, borrow_mut());
+}
+
+impl<NItem, O, S> Observer<NItem, S::Err>
+  for RetryWhenNotifierObserver<SharedState<O, S, S::Err>>
+where
+  O: Observer<S::Item, S::Err> + Send + Sync + 'static,
+  S: SharedObservable + Clone + Send + Sync + 'static,
+  S::Item: Send + Sync + 'static,
+  S::Err: PayloadCopy + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+{
+  notifier_observer_impl!(NItem, S::Err, shared_resubscribe, lock().unwrap());
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::cell::Cell;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn retry() {
+    let subscribed = Cell::new(0);
+    let mut values = vec![];
+    let mut errors = 0;
+    observable::create(|mut subscriber| {
+      subscribed.set(subscribed.get() + 1);
+      subscriber.next(subscribed.get());
+      subscriber.error("error");
+    })
+    .retry(2)
+    .subscribe_err(|v| values.push(v), |_| errors += 1);
+
+    assert_eq!(values, vec![1, 2, 3]);
+    assert_eq!(errors, 1);
+  }
+
+  #[test]
+  fn retry_until_complete() {
+    let subscribed = Cell::new(0);
+    let mut values = vec![];
+    let mut completed = 0;
+    observable::create(|mut subscriber| {
+      subscribed.set(subscribed.get() + 1);
+      if subscribed.get() < 3 {
+        subscriber.error(());
+      } else {
+        subscriber.next(subscribed.get());
+        subscriber.complete();
+      }
+    })
+    .retry(5)
+    .subscribe_complete(|v| values.push(v), || completed += 1);
+
+    assert_eq!(values, vec![3]);
+    assert_eq!(completed, 1);
+  }
+
+  #[test]
+  fn clean_failed_subscriptions() {
+    let mut source = Subject::new();
+    source.error("error");
+    let subscription = source
+      .clone()
+      .retry(3)
+      .subscribe_err(|_: i32| {}, |_: &str| {});
+
+    // the subject refuses late subscribers once it's errored, every attempt
+    // fails right away, and none is left behind.
+    assert_eq!(subscription.0.teardown_size(), 0);
+    assert!(subscription.is_closed());
+  }
+
+  #[test]
+  fn retry_when() {
+    let subscribed = Cell::new(0);
+    let mut values = vec![];
+    let mut completed = 0;
+    observable::create(|mut subscriber| {
+      subscribed.set(subscribed.get() + 1);
+      subscriber.next(subscribed.get());
+      subscriber.error(());
+    })
+    .retry_when(|errors| errors.take(2))
+    .subscribe_complete(|v| values.push(v), || completed += 1);
+
+    assert_eq!(values, vec![1, 2, 3]);
+    assert_eq!(completed, 1);
+  }
+
+  #[test]
+  fn retry_when_notifier_error() {
+    let subscribed = Cell::new(0);
+    let mut errors = vec![];
+    observable::create(|mut subscriber| {
+      subscribed.set(subscribed.get() + 1);
+      subscriber.error(subscribed.get());
+    })
+    .retry_when(|errors| {
+      errors.flat_map(|e| {
+        if e < 3 {
+          observable::of_result(Ok(()))
+        } else {
+          observable::of_result(Err(e))
+        }
+      })
+    })
+    .subscribe_err(|_: ()| {}, |e| errors.push(e));
+
+    assert_eq!(errors, vec![3]);
+  }
+
+  #[test]
+  fn backoff() {
+    let scheduler = TestScheduler::new();
+    let subscribed = Arc::new(Mutex::new(vec![]));
+    let c_subscribed = subscribed.clone();
+    let c_scheduler = scheduler.clone();
+    let delay_scheduler = scheduler.clone();
+    observable::create(move |mut subscriber| {
+      c_subscribed.lock().unwrap().push(c_scheduler.elapsed());
+      subscriber.error(());
+    })
+    .retry_when(move |errors| {
+      errors
+        .scan_initial(0, |attempt, _| attempt + 1)
+        .take(3)
+        .flat_map(move |attempt| {
+          let delay = Duration::from_millis(10 << attempt);
+          observable::of(()).delay_on(delay, delay_scheduler.clone())
+        })
+    })
+    .to_shared()
+    .subscribe(|_: ()| {});
+
+    scheduler.advance_by(Duration::from_secs(1));
+    assert_eq!(
+      *subscribed.lock().unwrap(),
+      vec![
+        Duration::from_millis(0),
+        Duration::from_millis(20),
+        Duration::from_millis(60),
+        Duration::from_millis(140),
+      ]
+    );
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let r = observable::of(1).retry(1);
+    r.clone().retry(1).subscribe(|_| {});
+    r.to_shared().subscribe(|_| {});
+    let w = observable::of(1).retry_when(|errors| errors);
+    w.clone().retry_when(|errors| errors).subscribe(|_| {});
+    w.subscribe(|_| {});
+    let w = observable::of(1).retry_when(|errors| errors);
+    w.clone()
+      .retry_when(|errors| errors)
+      .to_shared()
+      .subscribe(|_| {});
+    w.to_shared().subscribe(|_| {});
+  }
+}
@@ -0,0 +1,429 @@
+use crate::prelude::*;
+use observable::observable_proxy_impl;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Describes how [`retry_with_backoff`](Observable::retry_with_backoff)
+/// spaces out its retries.
+///
+/// The `n`th retry is delayed by `initial_delay * multiplier^n`, capped at
+/// `max_delay`, then shortened by a random part of at most `jitter` of it, so
+/// the retries of many subscribers failing together don't stay in sync.
+///
+/// # Example
+///
+/// ```
+/// # use rxrust::ops::retry_with_backoff::BackoffPolicy;
+/// # use std::time::Duration;
+/// let policy = BackoffPolicy {
+///   initial_delay: Duration::from_millis(10),
+///   max_attempts: 3,
+///   ..Default::default()
+/// };
+/// assert_eq!(policy.delay(0), Duration::from_millis(10));
+/// assert_eq!(policy.delay(2), Duration::from_millis(40));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackoffPolicy {
+  /// The delay before the first retry.
+  pub initial_delay: Duration,
+  /// The factor the delay grows by after each retry.
+  pub multiplier: f64,
+  /// The delay never grows beyond `max_delay`.
+  pub max_delay: Duration,
+  /// The ratio of the delay, between `0.` and `1.`, randomly removed from
+  /// it. `0.` disables the jitter.
+  pub jitter: f64,
+  /// How many times the source is resubscribed before its error is emitted.
+  pub max_attempts: usize,
+}
+
+impl Default for BackoffPolicy {
+  fn default() -> Self {
+    BackoffPolicy {
+      initial_delay: Duration::from_millis(100),
+      multiplier: 2.,
+      max_delay: Duration::from_secs(30),
+      jitter: 0.,
+      max_attempts: 5,
+    }
+  }
+}
+
+impl BackoffPolicy {
+  /// The delay before the `retry`th retry, counted from zero.
+  pub fn delay(&self, retry: usize) -> Duration {
+    let max = self.max_delay.as_secs_f64();
+    let exp = retry.min(i32::MAX as usize) as i32;
+    let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exp);
+    // an infinite or `NaN` delay overflowed the max delay.
+    let delay = if delay < max { delay.max(0.) } else { max };
+    // a `NaN` jitter disables it.
+    let jitter = if self.jitter > 0. { self.jitter.min(1.) } else { 0. };
+    if jitter == 0. {
+      Duration::from_secs_f64(delay)
+    } else {
+      Duration::from_secs_f64(delay * (1. - jitter * random()))
+    }
+  }
+}
+
+lazy_static! {
+  // Seeded once by the std hash map keys, so the jitters of the processes
+  // started together differ.
+  static ref JITTER_SEED: u64 = RandomState::new().build_hasher().finish();
+}
+
+static JITTER_COUNT: AtomicU64 = AtomicU64::new(0);
+
+/// A random number in `[0, 1)`, the next one of the splitmix64 sequence
+/// seeded by `JITTER_SEED`.
+fn random() -> f64 {
+  let n = JITTER_COUNT.fetch_add(1, Ordering::Relaxed);
+  (splitmix64(*JITTER_SEED, n) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The `n`th number of the splitmix64 sequence seeded by `seed`.
+fn splitmix64(seed: u64, n: u64) -> u64 {
+  let mut z =
+    seed.wrapping_add(n.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+  z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+  z ^ (z >> 31)
+}
+
+/// An Observable that mirrors the source, and resubscribes to it after a
+/// growing delay when it emits an error.
+///
+/// This struct is created by the retry_with_backoff method on
+/// [Observable](Observable::retry_with_backoff). See its documentation for
+/// more.
+#[derive(Clone)]
+pub struct RetryWithBackoffOp<S, SD = Schedulers> {
+  pub(crate) source: S,
+  pub(crate) policy: BackoffPolicy,
+  pub(crate) scheduler: SD,
+}
+
+observable_proxy_impl!(RetryWithBackoffOp, S, SD);
+
+/// Subscribes an attempt of `source`, after `retry` retries. The attempt has
+/// its own subscription, removed from the downstream one once the attempt
+/// fails.
+#[doc(hidden)]
+macro subscribe_attempt(
+  $subscription: ty,
+  $source: expr,
+  $policy: expr,
+  $scheduler: expr,
+  $retry: expr,
+  $observer: expr,
+  $downstream: expr
+) {{
+  let mut downstream = $downstream;
+  let mut attempt = <$subscription>::default();
+  downstream.add(attempt.clone());
+  let source = $source;
+  let unsub = source.clone().actual_subscribe(Subscriber {
+    observer: RetryWithBackoffObserver {
+      observer: Some($observer),
+      source: Some(source),
+      policy: $policy,
+      scheduler: $scheduler,
+      retry: $retry,
+      attempt: attempt.clone(),
+      subscription: downstream,
+    },
+    subscription: attempt.clone(),
+  });
+  attempt.add(unsub);
+}}
+
+impl<S, SD> SharedObservable for RetryWithBackoffOp<S, SD>
+where
+  S: SharedObservable + Clone + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let subscription = subscriber.subscription;
+    subscribe_attempt!(
+      SharedSubscription,
+      self.source,
+      self.policy,
+      self.scheduler,
+      0,
+      subscriber.observer,
+      subscription.clone()
+    );
+    subscription
+  }
+}
+
+impl<S, SD> LocalObservable<'static> for RetryWithBackoffOp<S, SD>
+where
+  S: LocalObservable<'static> + Clone + 'static,
+  SD: LocalScheduler + Clone + 'static,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let subscription = subscriber.subscription;
+    subscribe_attempt!(
+      LocalSubscription,
+      self.source,
+      self.policy,
+      self.scheduler,
+      0,
+      subscriber.observer,
+      subscription.clone()
+    );
+    subscription
+  }
+}
+
+pub struct RetryWithBackoffObserver<O, S, SD, U> {
+  // the observer and the source are handed over to the next attempt once this
+  // one fails.
+  observer: Option<O>,
+  source: Option<S>,
+  policy: BackoffPolicy,
+  scheduler: SD,
+  // how many retries were subscribed before this attempt.
+  retry: usize,
+  attempt: U,
+  subscription: U,
+}
+
+#[doc(hidden)]
+macro observer_impl($item: ident, $err: ident, $subscription: ty) {
+  fn next(&mut self, value: $item) {
+    if let Some(observer) = self.observer.as_mut() {
+      observer.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    self.attempt.unsubscribe();
+    self.subscription.remove(&self.attempt);
+    if let (Some(mut observer), Some(source)) =
+      (self.observer.take(), self.source.take())
+    {
+      if self.retry >= self.policy.max_attempts {
+        observer.error(err);
+        self.subscription.unsubscribe();
+      } else {
+        let delay = self.policy.delay(self.retry);
+        let policy = self.policy.clone();
+        let scheduler = self.scheduler.clone();
+        let retry = self.retry + 1;
+        let handle = self.scheduler.schedule(
+          move |tick, (observer, mut subscription)| {
+            subscription.remove(&tick);
+            if !subscription.is_closed() {
+              subscribe_attempt!(
+                $subscription,
+                source,
+                policy,
+                scheduler,
+                retry,
+                observer,
+                subscription
+              );
+            }
+          },
+          Some(delay),
+          (observer, self.subscription.clone()),
+        );
+        self.subscription.add(handle);
+      }
+    }
+  }
+
+  fn complete(&mut self) {
+    if let Some(mut observer) = self.observer.take() {
+      observer.complete();
+      self.subscription.unsubscribe();
+    }
+  }
+}
+
+impl<Item, Err, O, S, SD> Observer<Item, Err>
+  for RetryWithBackoffObserver<O, S, SD, SharedSubscription>
+where
+  O: Observer<Item, Err> + Send + Sync + 'static,
+  S: SharedObservable<Item = Item, Err = Err> + Clone + Send + Sync + 'static,
+  S::Unsub: Send + Sync,
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
+  observer_impl!(Item, Err, SharedSubscription);
+}
+
+impl<Item, Err, O, S, SD> Observer<Item, Err>
+  for RetryWithBackoffObserver<O, S, SD, LocalSubscription>
+where
+  O: Observer<Item, Err> + 'static,
+  S: LocalObservable<'static, Item = Item, Err = Err> + Clone + 'static,
+  SD: LocalScheduler + Clone + 'static,
+{
+  observer_impl!(Item, Err, LocalSubscription);
+}
+
+#[cfg(test)]
+mod test {
+  use super::BackoffPolicy;
+  use crate::prelude::*;
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn policy_delay() {
+    let policy = BackoffPolicy {
+      initial_delay: Duration::from_millis(10),
+      multiplier: 3.,
+      max_delay: Duration::from_millis(100),
+      ..Default::default()
+    };
+    assert_eq!(policy.delay(0), Duration::from_millis(10));
+    assert_eq!(policy.delay(1), Duration::from_millis(30));
+    assert_eq!(policy.delay(2), Duration::from_millis(90));
+    assert_eq!(policy.delay(3), Duration::from_millis(100));
+    assert_eq!(policy.delay(usize::MAX), Duration::from_millis(100));
+  }
+
+  #[test]
+  fn policy_jitter() {
+    let policy = BackoffPolicy {
+      initial_delay: Duration::from_millis(100),
+      jitter: 0.5,
+      ..Default::default()
+    };
+    for _ in 0..100 {
+      let delay = policy.delay(0);
+      assert!(delay > Duration::from_millis(50));
+      assert!(delay <= Duration::from_millis(100));
+    }
+  }
+
+  #[test]
+  fn jitter_spread() {
+    let values: Vec<_> = (0..1000).map(|_| super::random()).collect();
+    assert!(values.iter().all(|v| (0. ..1.).contains(v)));
+    let low = values.iter().filter(|v| **v < 0.5).count();
+    assert!(low > 400 && low < 600);
+    let mut distinct = values.clone();
+    distinct.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    distinct.dedup();
+    assert_eq!(distinct.len(), values.len());
+  }
+
+  #[test]
+  fn virtual_time() {
+    let scheduler = TestScheduler::new();
+    let subscribed = Arc::new(Mutex::new(vec![]));
+    let errors = Arc::new(Mutex::new(vec![]));
+    let c_subscribed = subscribed.clone();
+    let c_errors = errors.clone();
+    let c_scheduler = scheduler.clone();
+    let policy = BackoffPolicy {
+      initial_delay: Duration::from_millis(10),
+      max_delay: Duration::from_millis(30),
+      max_attempts: 4,
+      ..Default::default()
+    };
+    observable::create(move |mut subscriber| {
+      c_subscribed.lock().unwrap().push(c_scheduler.elapsed());
+      subscriber.error(());
+    })
+    .retry_with_backoff_on(policy, scheduler.clone())
+    .to_shared()
+    .subscribe_err(
+      |_: ()| {},
+      move |e| c_errors.lock().unwrap().push(e),
+    );
+
+    scheduler.advance_by(Duration::from_millis(89));
+    assert!(errors.lock().unwrap().is_empty());
+    scheduler.advance_by(Duration::from_millis(1));
+    assert_eq!(
+      *subscribed.lock().unwrap(),
+      vec![
+        Duration::from_millis(0),
+        Duration::from_millis(10),
+        Duration::from_millis(30),
+        Duration::from_millis(60),
+        Duration::from_millis(90),
+      ]
+    );
+    assert_eq!(*errors.lock().unwrap(), vec![()]);
+  }
+
+  #[test]
+  fn unsubscribe_cancel_retry() {
+    let scheduler = TestScheduler::new();
+    let subscribed = Arc::new(Mutex::new(0));
+    let c_subscribed = subscribed.clone();
+    let mut subscription = observable::create(move |mut subscriber| {
+      *c_subscribed.lock().unwrap() += 1;
+      subscriber.error(());
+    })
+    .retry_with_backoff_on(BackoffPolicy::default(), scheduler.clone())
+    .to_shared()
+    .subscribe_err(|_: ()| {}, |_| {});
+
+    subscription.unsubscribe();
+    scheduler.advance_by(Duration::from_secs(60));
+    assert_eq!(*subscribed.lock().unwrap(), 1);
+  }
+
+  #[test]
+  fn local() {
+    let scheduler = LocalPoolScheduler::new();
+    let subscribed = Rc::new(RefCell::new(0));
+    let values = Rc::new(RefCell::new(vec![]));
+    let c_subscribed = subscribed.clone();
+    let c_values = values.clone();
+    let policy = BackoffPolicy {
+      initial_delay: Duration::from_millis(1),
+      ..Default::default()
+    };
+    observable::create(move |mut subscriber| {
+      *c_subscribed.borrow_mut() += 1;
+      if *c_subscribed.borrow() < 3 {
+        subscriber.error(());
+      } else {
+        subscriber.next(*c_subscribed.borrow());
+        subscriber.complete();
+      }
+    })
+    .retry_with_backoff_on(policy, scheduler.clone())
+    .subscribe(move |v| c_values.borrow_mut().push(v));
+
+    scheduler.run();
+    assert_eq!(*values.borrow(), vec![3]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let scheduler = TestScheduler::new();
+    let r = observable::of(1)
+      .retry_with_backoff_on(BackoffPolicy::default(), scheduler.clone());
+    r.clone()
+      .retry_with_backoff_on(BackoffPolicy::default(), scheduler.clone())
+      .to_shared()
+      .subscribe(|_| {});
+    r.to_shared().subscribe(|_| {});
+  }
+}
@@ -0,0 +1,384 @@
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// An Observable that maps every item of the source to an inner observable
+/// and only mirrors the most recent one.
+///
+/// This struct is created by the switch_map method on
+/// [Observable](Observable::switch_map). See its documentation for more.
+#[derive(Clone)]
+pub struct SwitchMapOp<S, M> {
+  pub(crate) source: S,
+  pub(crate) func: M,
+}
+
+impl<S, M, Inner> Observable for SwitchMapOp<S, M>
+where
+  S: Observable,
+  M: FnMut(S::Item) -> Inner,
+  Inner: Observable<Err = S::Err>,
+{
+  type Item = Inner::Item;
+  type Err = S::Err;
+}
+
+impl<'a, S, M, Inner> LocalObservable<'a> for SwitchMapOp<S, M>
+where
+  S: LocalObservable<'a>,
+  M: FnMut(S::Item) -> Inner + 'a,
+  Inner: LocalObservable<'a, Err = S::Err>,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let state = Rc::new(RefCell::new(SwitchMapState::new(
+      Rc::new(RefCell::new(subscriber.observer)),
+      self.func,
+      subscription.clone(),
+    )));
+    subscription.add(self.source.actual_subscribe(Subscriber {
+      observer: SwitchMapOuterObserver(state),
+      subscription: LocalSubscription::default(),
+    }));
+    subscription
+  }
+}
+
+impl<S, M, Inner> SharedObservable for SwitchMapOp<S, M>
+where
+  S: SharedObservable,
+  S::Unsub: Send + Sync,
+  M: FnMut(S::Item) -> Inner + Send + 'static,
+  Inner: SharedObservable<Err = S::Err>,
+  Inner::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut subscription = subscriber.subscription;
+    let state = Arc::new(Mutex::new(SwitchMapState::new(
+      Arc::new(Mutex::new(subscriber.observer)),
+      self.func,
+      subscription.clone(),
+    )));
+    subscription.add(self.source.actual_subscribe(Subscriber {
+      observer: SwitchMapOuterObserver(state),
+      subscription: SharedSubscription::default(),
+    }));
+    subscription
+  }
+}
+
+struct SwitchMapState<O, M, U> {
+  // shared by the inner observers, which emit without holding the state: the
+  // downstream may feed the source again.
+  observer: O,
+  func: M,
+  // the downstream subscription.
+  subscription: U,
+  // the subscription of the inner observable currently mirrored.
+  active: Option<U>,
+  source_completed: bool,
+}
+
+impl<O, M, U> SwitchMapState<O, M, U> {
+  fn new(observer: O, func: M, subscription: U) -> Self {
+    SwitchMapState {
+      observer,
+      func,
+      subscription,
+      active: None,
+      source_completed: false,
+    }
+  }
+
+  #[inline]
+  fn is_done(&self) -> bool { self.source_completed && self.active.is_none() }
+}
+
+type LocalState<O, M> =
+  Rc<RefCell<SwitchMapState<Rc<RefCell<O>>, M, LocalSubscription>>>;
+type SharedState<O, M> =
+  Arc<Mutex<SwitchMapState<Arc<Mutex<O>>, M, SharedSubscription>>>;
+
+pub struct SwitchMapOuterObserver<S>(S);
+
+pub struct SwitchMapInnerObserver<S, U> {
+  state: S,
+  subscription: U,
+}
+
+#[doc(hidden)]
+macro outer_observer_impl(
+  $item: ident, $err: ident, $subscription: ty,
+  $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    let (inner, mut subscription) = {
+      let mut state = self.0.$($lock$($parentheses)?).+;
+      if let Some(mut previous) = state.active.take() {
+        previous.unsubscribe();
+        state.subscription.remove(&previous);
+      }
+      let subscription = <$subscription>::default();
+      state.active = Some(subscription.clone());
+      state.subscription.add(subscription.clone());
+      ((state.func)(value), subscription)
+    };
+    // don't hold the state while subscribing, the inner observable may emit
+    // synchronously.
+    let unsub = inner.actual_subscribe(Subscriber {
+      observer: SwitchMapInnerObserver {
+        state: self.0.clone(),
+        subscription: subscription.clone(),
+      },
+      subscription: subscription.clone(),
+    });
+    subscription.add(unsub);
+  }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    state.observer.$($lock$($parentheses)?).+.error(err);
+    state.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.0.$($lock$($parentheses)?).+;
+    state.source_completed = true;
+    if state.is_done() {
+      state.observer.$($lock$($parentheses)?).+.complete();
+      state.subscription.unsubscribe();
+    }
+  }
+}
+
+#[doc(hidden)]
+macro inner_observer_impl(
+  $item: ident, $err: ident, $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    if !self.subscription.is_closed() {
+      let observer = self.state.$($lock$($parentheses)?).+.observer.clone();
+      observer.$($lock$($parentheses)?).+.next(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    state.observer.$($lock$($parentheses)?).+.error(err);
+    state.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    let active = state.active.as_ref().map(|s| s.inner_addr());
+    if active == Some(self.subscription.inner_addr()) {
+      state.active = None;
+      state.subscription.remove(&self.subscription);
+      if state.is_done() {
+        state.observer.$($lock$($parentheses)?).+.complete();
+        state.subscription.unsubscribe();
+      }
+    }
+  }
+}
+
+impl<'a, Item, Err, O, M, Inner> Observer<Item, Err>
+  for SwitchMapOuterObserver<LocalState<O, M>>
+where
+  O: Observer<Inner::Item, Err> + 'a,
+  M: FnMut(Item) -> Inner + 'a,
+  Inner: LocalObservable<'a, Err = Err>,
+{
+  outer_observer_impl!(Item, Err, LocalSubscription, borrow_mut());
+}
+
+impl<Item, Err, O, M, Inner> Observer<Item, Err>
+  for SwitchMapOuterObserver<SharedState<O, M>>
+where
+  O: Observer<Inner::Item, Err> + Send + Sync + 'static,
+  M: FnMut(Item) -> Inner + Send + 'static,
+  Inner: SharedObservable<Err = Err>,
+  Inner::Unsub: Send + Sync,
+{
+  outer_observer_impl!(Item, Err, SharedSubscription, lock().unwrap());
+}
+
+impl<Item, Err, O, M> Observer<Item, Err>
+  for SwitchMapInnerObserver<LocalState<O, M>, LocalSubscription>
+where
+  O: Observer<Item, Err>,
+{
+  inner_observer_impl!(Item, Err, borrow_mut());
+}
+
+impl<Item, Err, O, M> Observer<Item, Err>
+  for SwitchMapInnerObserver<SharedState<O, M>, SharedSubscription>
+where
+  O: Observer<Item, Err>,
+{
+  inner_observer_impl!(Item, Err, lock().unwrap());
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  };
+  use std::time::Duration;
+
+  #[test]
+  fn smoke() {
+    let mut values = vec![];
+    let mut completed = false;
+    observable::from_iter(0..3)
+      .switch_map(|v| observable::from_iter(vec![v; 2]))
+      .subscribe_complete(|v| values.push(v), || completed = true);
+
+    assert_eq!(values, vec![0, 0, 1, 1, 2, 2]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn switch_to_latest() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut source = Subject::new();
+      let mut a = Subject::new();
+      let mut b = Subject::new();
+      let inners = [a.clone(), b.clone()];
+      source
+        .clone()
+        .switch_map(move |i: usize| inners[i].clone())
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      source.next(0);
+      a.next(1);
+      source.next(1);
+      // `a` is unsubscribed once `b` is active.
+      a.next(2);
+      b.next(3);
+      a.complete();
+      source.complete();
+      assert_eq!(a.subscribed_size(), 0);
+      b.next(4);
+      b.complete();
+    }
+    assert_eq!(values, vec![1, 3, 4]);
+    assert!(completed);
+  }
+
+  #[test]
+  fn wait_inner_complete() {
+    let completed = Arc::new(AtomicBool::new(false));
+    let c_completed = completed.clone();
+    let mut source = Subject::new();
+    let mut inner = Subject::new();
+    let c_inner = inner.clone();
+    source
+      .clone()
+      .switch_map(move |_: ()| c_inner.clone())
+      .subscribe_complete(
+        |_: ()| {},
+        move || c_completed.store(true, Ordering::Relaxed),
+      );
+
+    source.next(());
+    source.complete();
+    assert!(!completed.load(Ordering::Relaxed));
+    inner.complete();
+    assert!(completed.load(Ordering::Relaxed));
+  }
+
+  #[test]
+  fn unsubscribe() {
+    let mut source = Subject::new();
+    let inner = Subject::new();
+    let c_inner = inner.clone();
+    let mut subscription = source
+      .clone()
+      .switch_map(move |_: ()| c_inner.clone())
+      .subscribe(|_: i32| {});
+    source.next(());
+    assert_eq!(inner.subscribed_size(), 1);
+    subscription.unsubscribe();
+    assert!(inner.observers.borrow().list.iter().all(|o| o.is_closed()));
+  }
+
+  #[test]
+  fn error() {
+    let mut errors = 0;
+    {
+      let mut source = Subject::new();
+      let mut inner = Subject::new();
+      let c_inner = inner.clone();
+      source
+        .clone()
+        .switch_map(move |_: ()| c_inner.clone())
+        .subscribe_err(|_: i32| {}, |_| errors += 1);
+      source.next(());
+      inner.error("");
+      source.error("");
+    }
+    assert_eq!(errors, 1);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    observable::from_iter(0..3)
+      .switch_map(observable::of)
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    assert_eq!(*values.lock().unwrap(), vec![0, 1, 2]);
+  }
+
+  #[test]
+  fn feed_source_from_downstream() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut source: SharedSubject<i32, ()> = Subject::new();
+    let mut c_source = source.clone();
+    let c_scheduler = scheduler.clone();
+    source
+      .clone()
+      .switch_map(move |v| {
+        let delay = Duration::from_millis(1);
+        observable::of(v).delay_on(delay, c_scheduler.clone())
+      })
+      .to_shared()
+      .subscribe(move |v| {
+        c_values.lock().unwrap().push(v);
+        if v < 3 {
+          c_source.next(v + 1);
+        }
+      });
+
+    source.next(0);
+    scheduler.advance_by(Duration::from_millis(10));
+    assert_eq!(*values.lock().unwrap(), vec![0, 1, 2, 3]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let m = observable::from_iter(0..10).switch_map(observable::of);
+    m.clone().switch_map(observable::of).subscribe(|_| {});
+    m.switch_map(observable::of).to_shared().subscribe(|_| {});
+  }
+}
@@ -1,5 +1,7 @@
 use crate::prelude::*;
 use observable::observable_proxy_impl;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
@@ -11,19 +13,21 @@ pub enum ThrottleEdge {
 }
 
 #[derive(Clone)]
-pub struct ThrottleTimeOp<S> {
+pub struct ThrottleTimeOp<S, SD = Schedulers> {
   pub(crate) source: S,
   pub(crate) duration: Duration,
   pub(crate) edge: ThrottleEdge,
+  pub(crate) scheduler: SD,
 }
 
-observable_proxy_impl!(ThrottleTimeOp, S);
+observable_proxy_impl!(ThrottleTimeOp, S, SD);
 
-impl<Item, Err, S, Unsub> SharedObservable for ThrottleTimeOp<S>
+impl<Item, Err, S, Unsub, SD> SharedObservable for ThrottleTimeOp<S, SD>
 where
   S: for<'r> LocalObservable<'r, Item = Item, Err = Err, Unsub = Unsub>,
   Item: Clone + Send + 'static,
   Unsub: SubscriptionLike + 'static,
+  SD: Scheduler + Send + 'static,
 {
   type Unsub = Unsub;
   fn actual_subscribe<
@@ -36,6 +40,7 @@ where
       source,
       duration,
       edge,
+      scheduler,
     } = self;
     let mut subscription = LocalSubscription::default();
     subscription.add(subscriber.subscription.clone());
@@ -44,6 +49,7 @@ where
         InnerThrottleTimeObserver {
           observer: subscriber.observer,
           edge,
+          scheduler,
           delay: duration,
           trailing_value: None,
           throttled: None,
@@ -72,10 +78,11 @@ where
 //   .to_shared()
 //   .subscribe(move |v| println!("{}", v));
 // ```
-impl<S> SharedObservable for ThrottleTimeOp<Shared<S>>
+impl<S, SD> SharedObservable for ThrottleTimeOp<Shared<S>, SD>
 where
   S: SharedObservable,
   S::Item: Clone + Send + 'static,
+  SD: Scheduler + Send + 'static,
 {
   type Unsub = S::Unsub;
   fn actual_subscribe<
@@ -88,6 +95,7 @@ where
       source,
       duration,
       edge,
+      scheduler,
     } = self;
     let Subscriber {
       observer,
@@ -98,6 +106,7 @@ where
         InnerThrottleTimeObserver {
           observer,
           edge,
+          scheduler,
           delay: duration,
           trailing_value: None,
           throttled: None,
@@ -109,23 +118,67 @@ where
   }
 }
 
-struct InnerThrottleTimeObserver<O, Item> {
+impl<S, SD> LocalObservable<'static> for ThrottleTimeOp<S, SD>
+where
+  S: LocalObservable<'static>,
+  S::Item: Clone + 'static,
+  SD: LocalScheduler + 'static,
+{
+  type Unsub = S::Unsub;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let Self {
+      source,
+      duration,
+      edge,
+      scheduler,
+    } = self;
+    let Subscriber {
+      observer,
+      subscription,
+    } = subscriber;
+    source.actual_subscribe(Subscriber {
+      observer: LocalThrottleTimeObserver(Rc::new(RefCell::new(
+        InnerThrottleTimeObserver {
+          observer,
+          edge,
+          scheduler,
+          delay: duration,
+          trailing_value: None,
+          throttled: None,
+          subscription: subscription.clone(),
+        },
+      ))),
+      subscription,
+    })
+  }
+}
+
+struct InnerThrottleTimeObserver<O, Item, SD, U = SharedSubscription> {
   observer: O,
   edge: ThrottleEdge,
+  scheduler: SD,
   delay: Duration,
   trailing_value: Option<Item>,
-  throttled: Option<SharedSubscription>,
-  subscription: SharedSubscription,
+  throttled: Option<U>,
+  subscription: U,
 }
 
-pub struct ThrottleTimeObserver<O, Item>(
-  Arc<Mutex<InnerThrottleTimeObserver<O, Item>>>,
+pub struct ThrottleTimeObserver<O, Item, SD>(
+  Arc<Mutex<InnerThrottleTimeObserver<O, Item, SD>>>,
 );
 
-impl<O, Item, Err> Observer<Item, Err> for ThrottleTimeObserver<O, Item>
+pub struct LocalThrottleTimeObserver<O, Item, SD>(
+  Rc<RefCell<InnerThrottleTimeObserver<O, Item, SD, LocalSubscription>>>,
+);
+
+impl<O, Item, Err, SD> Observer<Item, Err> for ThrottleTimeObserver<O, Item, SD>
 where
   O: Observer<Item, Err> + Send + 'static,
   Item: Clone + Send + 'static,
+  SD: Scheduler + Send + 'static,
 {
   fn next(&mut self, value: Item) {
     let mut inner = self.0.lock().unwrap();
@@ -135,7 +188,7 @@ where
 
     if inner.throttled.is_none() {
       let c_inner = self.0.clone();
-      let subscription = Schedulers::ThreadPool.schedule(
+      let subscription = inner.scheduler.schedule(
         move |_, _| {
           let mut inner = c_inner.lock().unwrap();
           if let Some(v) = inner.trailing_value.take() {
@@ -171,6 +224,54 @@ where
   }
 }
 
+impl<O, Item, Err, SD> Observer<Item, Err>
+  for LocalThrottleTimeObserver<O, Item, SD>
+where
+  O: Observer<Item, Err> + 'static,
+  Item: Clone + 'static,
+  SD: LocalScheduler + 'static,
+{
+  fn next(&mut self, value: Item) {
+    let mut inner = self.0.borrow_mut();
+    if inner.edge == ThrottleEdge::Tailing {
+      inner.trailing_value = Some(value.clone());
+    }
+
+    if inner.throttled.is_none() {
+      let c_inner = self.0.clone();
+      let subscription = inner.scheduler.schedule(
+        move |_, _| {
+          let mut inner = c_inner.borrow_mut();
+          if let Some(v) = inner.trailing_value.take() {
+            inner.observer.next(v);
+          }
+          if let Some(mut throttled) = inner.throttled.take() {
+            throttled.unsubscribe();
+            inner.subscription.remove(&throttled);
+          }
+        },
+        Some(inner.delay),
+        (),
+      );
+      inner.subscription.add(subscription.clone());
+      inner.throttled = Some(subscription);
+      if inner.edge == ThrottleEdge::Leading {
+        inner.observer.next(value);
+      }
+    }
+  }
+
+  fn error(&mut self, err: Err) { self.0.borrow_mut().observer.error(err) }
+
+  fn complete(&mut self) {
+    let mut inner = self.0.borrow_mut();
+    if let Some(value) = inner.trailing_value.take() {
+      inner.observer.next(value);
+    }
+    inner.observer.complete();
+  }
+}
+
 #[test]
 fn smoke() {
   let x = Arc::new(Mutex::new(vec![]));
@@ -206,6 +307,50 @@ fn smoke() {
   );
 }
 
+#[test]
+fn virtual_time() {
+  let scheduler = TestScheduler::new();
+  let x = Arc::new(Mutex::new(vec![]));
+  let x_c = x.clone();
+
+  let mut sub =
+    observable::interval_on(Duration::from_millis(5), scheduler.clone())
+      .to_shared()
+      .throttle_time_on(
+        Duration::from_millis(48),
+        ThrottleEdge::Tailing,
+        scheduler.clone(),
+      )
+      .to_shared()
+      .subscribe(move |v| x.lock().unwrap().push(v));
+
+  scheduler.advance_by(Duration::from_millis(520));
+  sub.unsubscribe();
+  assert_eq!(
+    x_c.lock().unwrap().clone(),
+    vec![9, 19, 29, 39, 49, 59, 69, 79, 89, 99]
+  );
+}
+
+#[test]
+fn local() {
+  use crate::scheduler::test::LocalTestScheduler;
+
+  let scheduler = TestScheduler::new();
+  let local = LocalTestScheduler(scheduler.clone());
+  let x = Rc::new(RefCell::new(vec![]));
+  let x_c = x.clone();
+
+  observable::interval_on(Duration::from_millis(10), local.clone())
+    .take(20)
+    .map(Rc::new)
+    .throttle_time_on(Duration::from_millis(95), ThrottleEdge::Leading, local)
+    .subscribe(move |v| x.borrow_mut().push(*v));
+
+  scheduler.advance_by(Duration::from_millis(200));
+  assert_eq!(*x_c.borrow(), vec![0, 10]);
+}
+
 #[test]
 fn fork_and_shared() {
   observable::of(0..10)
@@ -0,0 +1,751 @@
+use crate::prelude::*;
+use observable::observable_proxy_impl;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The error emitted by [`timeout`](Observable::timeout).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeoutError<Err> {
+  /// No item arrived in time.
+  Elapsed,
+  /// The source emitted an error.
+  Source(Err),
+}
+
+/// An Observable that mirrors the source, and emits a
+/// [`TimeoutError::Elapsed`] error when the source doesn't emit an item in
+/// time.
+///
+/// This struct is created by the timeout method on
+/// [Observable](Observable::timeout). See its documentation for more.
+#[derive(Clone)]
+pub struct TimeoutOp<S, SD = Schedulers> {
+  pub(crate) source: S,
+  pub(crate) dur: Duration,
+  pub(crate) scheduler: SD,
+}
+
+impl<S, SD> Observable for TimeoutOp<S, SD>
+where
+  S: Observable,
+{
+  type Item = S::Item;
+  type Err = TimeoutError<S::Err>;
+}
+
+/// An Observable that mirrors the source, and switches to a fallback
+/// observable when the source doesn't emit an item in time.
+///
+/// This struct is created by the timeout_with method on
+/// [Observable](Observable::timeout_with). See its documentation for more.
+#[derive(Clone)]
+pub struct TimeoutWithOp<S, F, SD = Schedulers> {
+  pub(crate) source: S,
+  pub(crate) dur: Duration,
+  pub(crate) fallback: F,
+  pub(crate) scheduler: SD,
+}
+
+observable_proxy_impl!(TimeoutWithOp, S, F, SD);
+
+impl<S, SD> LocalObservable<'static> for TimeoutOp<S, SD>
+where
+  S: LocalObservable<'static>,
+  S::Item: 'static,
+  S::Err: 'static,
+  SD: LocalScheduler + Clone + 'static,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    local_subscribe(
+      self.source,
+      self.dur,
+      self.scheduler,
+      Subscriber {
+        observer: TimeoutErrorObserver(subscriber.observer),
+        subscription: subscriber.subscription,
+      },
+      |mut observer: TimeoutErrorObserver<O>,
+       mut subscription: LocalSubscription| {
+        observer.0.error(TimeoutError::Elapsed);
+        subscription.unsubscribe();
+      },
+    )
+  }
+}
+
+impl<S, SD> SharedObservable for TimeoutOp<S, SD>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+  S::Unsub: Send + Sync,
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    shared_subscribe(
+      self.source,
+      self.dur,
+      self.scheduler,
+      Subscriber {
+        observer: TimeoutErrorObserver(subscriber.observer),
+        subscription: subscriber.subscription,
+      },
+      |mut observer: TimeoutErrorObserver<O>,
+       mut subscription: SharedSubscription| {
+        observer.0.error(TimeoutError::Elapsed);
+        subscription.unsubscribe();
+      },
+    )
+  }
+}
+
+impl<S, F, SD> LocalObservable<'static> for TimeoutWithOp<S, F, SD>
+where
+  S: LocalObservable<'static>,
+  S::Item: 'static,
+  S::Err: 'static,
+  F: LocalObservable<'static, Item = S::Item, Err = S::Err> + 'static,
+  SD: LocalScheduler + Clone + 'static,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'static>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let fallback = self.fallback;
+    local_subscribe(
+      self.source,
+      self.dur,
+      self.scheduler,
+      subscriber,
+      move |observer: O, mut subscription: LocalSubscription| {
+        subscription.add(fallback.actual_subscribe(Subscriber {
+          observer,
+          subscription: LocalSubscription::default(),
+        }));
+      },
+    )
+  }
+}
+
+impl<S, F, SD> SharedObservable for TimeoutWithOp<S, F, SD>
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+  S::Unsub: Send + Sync,
+  F: SharedObservable<Item = S::Item, Err = S::Err> + Send + Sync + 'static,
+  F::Unsub: Send + Sync,
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let fallback = self.fallback;
+    shared_subscribe(
+      self.source,
+      self.dur,
+      self.scheduler,
+      subscriber,
+      move |observer: O, mut subscription: SharedSubscription| {
+        subscription.add(fallback.actual_subscribe(Subscriber {
+          observer,
+          subscription: SharedSubscription::default(),
+        }));
+      },
+    )
+  }
+}
+
+/// Forwards the errors of the source wrapped in [`TimeoutError::Source`].
+pub struct TimeoutErrorObserver<O>(O);
+
+impl<Item, Err, O> Observer<Item, Err> for TimeoutErrorObserver<O>
+where
+  O: Observer<Item, TimeoutError<Err>>,
+{
+  #[inline]
+  fn next(&mut self, value: Item) { self.0.next(value); }
+
+  #[inline]
+  fn error(&mut self, err: Err) { self.0.error(TimeoutError::Source(err)); }
+
+  #[inline]
+  fn complete(&mut self) { self.0.complete(); }
+}
+
+pub struct TimeoutState<O, F, SD, U, Item, Err> {
+  // taken out while emitting, the state isn't held then since the downstream
+  // may feed the source again.
+  observer: Option<O>,
+  emitting: bool,
+  // the notifications fed back by the downstream while it's emitting.
+  queue: VecDeque<Notification<Item, Err>>,
+  // called with the observer once the timeout elapsed.
+  on_timeout: Option<F>,
+  scheduler: SD,
+  dur: Duration,
+  // bumped by each item, a timer only fires if no item arrived since it was
+  // scheduled.
+  index: usize,
+  timer: Option<U>,
+  // the subscription of the source, unsubscribed once the timeout elapsed.
+  source: U,
+  subscription: U,
+}
+
+impl<O, F, SD, U, Item, Err> TimeoutState<O, F, SD, U, Item, Err> {
+  /// Takes the observer out to emit `n`, or queues `n` if the observer is
+  /// already emitting.
+  fn take_observer(
+    &mut self,
+    n: Notification<Item, Err>,
+  ) -> Option<(O, Notification<Item, Err>)> {
+    if self.emitting {
+      self.queue.push_back(n);
+      None
+    } else {
+      let observer = self.observer.take()?;
+      self.emitting = true;
+      Some((observer, n))
+    }
+  }
+}
+
+type LocalState<O, F, SD, Item, Err> =
+  Rc<RefCell<TimeoutState<O, F, SD, LocalSubscription, Item, Err>>>;
+type SharedState<O, F, SD, Item, Err> =
+  Arc<Mutex<TimeoutState<O, F, SD, SharedSubscription, Item, Err>>>;
+
+/// Subscribes the source, and starts the timer of its first item.
+#[doc(hidden)]
+macro subscribe_impl(
+  $source: ident,
+  $dur: ident,
+  $scheduler: ident,
+  $subscriber: ident,
+  $on_timeout: ident,
+  $state_ctor: ident,
+  $subscription: ty,
+  $schedule: ident
+) {{
+  let mut subscription = $subscriber.subscription;
+  let mut upstream = <$subscription>::default();
+  subscription.add(upstream.clone());
+  let state = $state_ctor(TimeoutState {
+    observer: Some($subscriber.observer),
+    emitting: false,
+    queue: VecDeque::new(),
+    on_timeout: Some($on_timeout),
+    scheduler: $scheduler,
+    dur: $dur,
+    index: 0,
+    timer: None,
+    source: upstream.clone(),
+    subscription: subscription.clone(),
+  });
+  $schedule(&state);
+  upstream.add($source.actual_subscribe(Subscriber {
+    observer: TimeoutObserver(state),
+    subscription: upstream.clone(),
+  }));
+  subscription
+}}
+
+fn local_subscribe<S, O, F, SD>(
+  source: S,
+  dur: Duration,
+  scheduler: SD,
+  subscriber: Subscriber<O, LocalSubscription>,
+  on_timeout: F,
+) -> LocalSubscription
+where
+  S: LocalObservable<'static>,
+  S::Item: 'static,
+  S::Err: 'static,
+  O: Observer<S::Item, S::Err> + 'static,
+  F: FnOnce(O, LocalSubscription) + 'static,
+  SD: LocalScheduler + Clone + 'static,
+{
+  let new_state = |state| Rc::new(RefCell::new(state));
+  subscribe_impl!(
+    source,
+    dur,
+    scheduler,
+    subscriber,
+    on_timeout,
+    new_state,
+    LocalSubscription,
+    local_schedule_timer
+  )
+}
+
+fn shared_subscribe<S, O, F, SD>(
+  source: S,
+  dur: Duration,
+  scheduler: SD,
+  subscriber: Subscriber<O, SharedSubscription>,
+  on_timeout: F,
+) -> SharedSubscription
+where
+  S: SharedObservable,
+  S::Item: Send + 'static,
+  S::Err: Send + 'static,
+  S::Unsub: Send + Sync,
+  O: Observer<S::Item, S::Err> + Send + Sync + 'static,
+  F: FnOnce(O, SharedSubscription) + Send + 'static,
+  SD: Scheduler + Clone + Send + Sync + 'static,
+{
+  let new_state = |state| Arc::new(Mutex::new(state));
+  subscribe_impl!(
+    source,
+    dur,
+    scheduler,
+    subscriber,
+    on_timeout,
+    new_state,
+    SharedSubscription,
+    shared_schedule_timer
+  )
+}
+
+/// Schedules the timer of the next item, unless the downstream is done.
+#[doc(hidden)]
+macro schedule_timer_impl(
+  $state: ident, $fire: ident, $($lock: tt $($parentheses: tt)?).+
+) {
+  let (scheduler, dur, index) = {
+    let inner = $state.$($lock$($parentheses)?).+;
+    if inner.observer.is_none() {
+      return;
+    }
+    (inner.scheduler.clone(), inner.dur, inner.index)
+  };
+  // don't hold the state while scheduling, the scheduler may run the timer
+  // right away.
+  let c_state = $state.clone();
+  let mut timer =
+    scheduler.schedule(move |_, _| $fire(&c_state, index), Some(dur), ());
+  let mut inner = $state.$($lock$($parentheses)?).+;
+  if inner.index == index && inner.observer.is_some() {
+    inner.subscription.add(timer.clone());
+    inner.timer = Some(timer);
+  } else {
+    timer.unsubscribe();
+  }
+}
+
+/// Hands the observer over to `on_timeout`, unless an item arrived since the
+/// timer was scheduled.
+#[doc(hidden)]
+macro fire_impl(
+  $state: ident, $index: ident, $($lock: tt $($parentheses: tt)?).+
+) {
+  let fired = {
+    let mut inner = $state.$($lock$($parentheses)?).+;
+    if inner.index != $index {
+      return;
+    }
+    if let Some(timer) = inner.timer.take() {
+      inner.subscription.remove(&timer);
+    }
+    match (inner.observer.take(), inner.on_timeout.take()) {
+      (Some(observer), Some(on_timeout)) => {
+        inner.source.unsubscribe();
+        Some((observer, on_timeout, inner.subscription.clone()))
+      }
+      _ => None,
+    }
+  };
+  if let Some((observer, on_timeout, subscription)) = fired {
+    on_timeout(observer, subscription);
+  }
+}
+
+fn local_schedule_timer<O, F, SD, Item, Err>(
+  state: &LocalState<O, F, SD, Item, Err>,
+) where
+  O: 'static,
+  F: FnOnce(O, LocalSubscription) + 'static,
+  SD: LocalScheduler + Clone + 'static,
+  Item: 'static,
+  Err: 'static,
+{
+  schedule_timer_impl!(state, local_fire, borrow_mut());
+}
+
+fn shared_schedule_timer<O, F, SD, Item, Err>(
+  state: &SharedState<O, F, SD, Item, Err>,
+) where
+  O: Send + 'static,
+  F: FnOnce(O, SharedSubscription) + Send + 'static,
+  SD: Scheduler + Clone + Send + 'static,
+  Item: Send + 'static,
+  Err: Send + 'static,
+{
+  schedule_timer_impl!(state, shared_fire, lock().unwrap());
+}
+
+fn local_fire<O, F, SD, Item, Err>(
+  state: &LocalState<O, F, SD, Item, Err>,
+  index: usize,
+) where
+  F: FnOnce(O, LocalSubscription),
+{
+  fire_impl!(state, index, borrow_mut());
+}
+
+fn shared_fire<O, F, SD, Item, Err>(
+  state: &SharedState<O, F, SD, Item, Err>,
+  index: usize,
+) where
+  F: FnOnce(O, SharedSubscription),
+{
+  fire_impl!(state, index, lock().unwrap());
+}
+
+/// Observes the source, and restarts the timer on each item.
+pub struct TimeoutObserver<St>(St);
+
+#[doc(hidden)]
+macro observer_impl(
+  $item: ident,
+  $err: ident,
+  $schedule: ident,
+  $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    let taken = {
+      let mut inner = self.0.$($lock$($parentheses)?).+;
+      inner.index += 1;
+      if let Some(mut timer) = inner.timer.take() {
+        timer.unsubscribe();
+        inner.subscription.remove(&timer);
+      }
+      inner.take_observer(Notification::Next(value))
+    };
+    if let Some((observer, n)) = taken {
+      emit_impl!(self.0, observer, n, $schedule, $($lock $($parentheses)?).+);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    let taken = self
+      .0
+      .$($lock$($parentheses)?).+
+      .take_observer(Notification::Error(err));
+    if let Some((observer, n)) = taken {
+      emit_impl!(self.0, observer, n, $schedule, $($lock $($parentheses)?).+);
+    }
+  }
+
+  fn complete(&mut self) {
+    let taken = self
+      .0
+      .$($lock$($parentheses)?).+
+      .take_observer(Notification::Complete);
+    if let Some((observer, n)) = taken {
+      emit_impl!(self.0, observer, n, $schedule, $($lock $($parentheses)?).+);
+    }
+  }
+}
+
+/// Emits `n` and the notifications queued meanwhile without holding the
+/// state, then gives the observer back and restarts the timer, unless the
+/// source is done.
+#[doc(hidden)]
+macro emit_impl(
+  $state: expr,
+  $observer: ident,
+  $n: ident,
+  $schedule: ident,
+  $($lock: tt $($parentheses: tt)?).+
+) {{
+  let mut observer = $observer;
+  let mut n = $n;
+  loop {
+    let terminal = match n {
+      Notification::Next(_) => false,
+      _ => true,
+    };
+    n.notify(&mut observer);
+    let mut inner = $state.$($lock$($parentheses)?).+;
+    if terminal {
+      inner.emitting = false;
+      inner.queue.clear();
+      inner.on_timeout.take();
+      inner.subscription.unsubscribe();
+      return;
+    }
+    match inner.queue.pop_front() {
+      Some(queued) => n = queued,
+      None => {
+        inner.observer = Some(observer);
+        inner.emitting = false;
+        break;
+      }
+    }
+  }
+  $schedule(&$state);
+}}
+
+impl<Item, Err, O, F, SD> Observer<Item, Err>
+  for TimeoutObserver<LocalState<O, F, SD, Item, Err>>
+where
+  O: Observer<Item, Err> + 'static,
+  F: FnOnce(O, LocalSubscription) + 'static,
+  SD: LocalScheduler + Clone + 'static,
+  Item: 'static,
+  Err: 'static,
+{
+  observer_impl!(Item, Err, local_schedule_timer, borrow_mut());
+}
+
+impl<Item, Err, O, F, SD> Observer<Item, Err>
+  for TimeoutObserver<SharedState<O, F, SD, Item, Err>>
+where
+  O: Observer<Item, Err> + Send + 'static,
+  F: FnOnce(O, SharedSubscription) + Send + 'static,
+  SD: Scheduler + Clone + Send + 'static,
+  Item: Send + 'static,
+  Err: Send + 'static,
+{
+  observer_impl!(Item, Err, shared_schedule_timer, lock().unwrap());
+}
+
+#[cfg(test)]
+mod test {
+  use super::{SharedState, TimeoutError, TimeoutObserver, TimeoutState};
+  use crate::prelude::*;
+  use std::cell::RefCell;
+  use std::collections::VecDeque;
+  use std::rc::Rc;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn elapsed() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let errors = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let c_errors = errors.clone();
+    let mut subject = SharedSubject::new();
+    subject
+      .clone()
+      .timeout_on(Duration::from_millis(10), scheduler.clone())
+      .to_shared()
+      .subscribe_err(
+        move |v| c_values.lock().unwrap().push(v),
+        move |e| c_errors.lock().unwrap().push(e),
+      );
+
+    scheduler.advance_by(Duration::from_millis(9));
+    subject.next(1);
+    scheduler.advance_by(Duration::from_millis(9));
+    subject.next(2);
+    scheduler.advance_by(Duration::from_millis(9));
+    assert!(errors.lock().unwrap().is_empty());
+    scheduler.advance_by(Duration::from_millis(1));
+    subject.next(3);
+
+    assert_eq!(*values.lock().unwrap(), vec![1, 2]);
+    assert_eq!(
+      *errors.lock().unwrap(),
+      vec![TimeoutError::<()>::Elapsed]
+    );
+  }
+
+  #[test]
+  fn source_error() {
+    let scheduler = TestScheduler::new();
+    let errors = Arc::new(Mutex::new(vec![]));
+    let c_errors = errors.clone();
+    observable::throw("error")
+      .timeout_on(Duration::from_millis(10), scheduler.clone())
+      .to_shared()
+      .subscribe_err(|_| {}, move |e| c_errors.lock().unwrap().push(e));
+
+    scheduler.advance_by(Duration::from_millis(10));
+    assert_eq!(*errors.lock().unwrap(), vec![TimeoutError::Source("error")]);
+  }
+
+  #[test]
+  fn complete_in_time() {
+    let scheduler = TestScheduler::new();
+    let completed = Arc::new(Mutex::new(0));
+    let c_completed = completed.clone();
+    let mut subscription = observable::from_iter(0..3)
+      .timeout_on(Duration::from_millis(10), scheduler.clone())
+      .to_shared()
+      .subscribe_all(
+        |_| {},
+        |_| panic!("no error"),
+        move || *c_completed.lock().unwrap() += 1,
+      );
+
+    scheduler.advance_by(Duration::from_millis(20));
+    assert_eq!(*completed.lock().unwrap(), 1);
+    assert!(subscription.is_closed());
+    subscription.unsubscribe();
+  }
+
+  #[test]
+  fn switch_to_fallback() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut subject = SharedSubject::new();
+    let mut fallback = SharedSubject::new();
+    subject
+      .clone()
+      .timeout_with_on(
+        Duration::from_millis(10),
+        fallback.clone(),
+        scheduler.clone(),
+      )
+      .to_shared()
+      .subscribe(move |v| c_values.lock().unwrap().push(v));
+
+    subject.next(1);
+    fallback.next(-1);
+    scheduler.advance_by(Duration::from_millis(10));
+    subject.next(2);
+    fallback.next(-2);
+
+    assert_eq!(*values.lock().unwrap(), vec![1, -2]);
+  }
+
+  #[test]
+  fn unsubscribe_cancel_timer() {
+    let scheduler = TestScheduler::new();
+    let errors = Arc::new(Mutex::new(0));
+    let c_errors = errors.clone();
+    let mut subscription = observable::never()
+      .timeout_on(Duration::from_millis(10), scheduler.clone())
+      .to_shared()
+      .subscribe_err(
+        |_: ()| {},
+        move |_: TimeoutError<()>| *c_errors.lock().unwrap() += 1,
+      );
+
+    subscription.unsubscribe();
+    scheduler.advance_by(Duration::from_millis(10));
+    assert_eq!(*errors.lock().unwrap(), 0);
+  }
+
+  type FeedbackState = SharedState<
+    Feedback,
+    fn(Feedback, SharedSubscription),
+    TestScheduler,
+    i32,
+    (),
+  >;
+
+  // feeds the timeout again from `next`, while the timeout is emitting.
+  struct Feedback {
+    state: Arc<Mutex<Option<FeedbackState>>>,
+    values: Arc<Mutex<Vec<i32>>>,
+    errors: Arc<Mutex<usize>>,
+  }
+
+  impl Observer<i32, ()> for Feedback {
+    fn next(&mut self, value: i32) {
+      self.values.lock().unwrap().push(value);
+      if value < 3 {
+        let state = self.state.lock().unwrap().clone().unwrap();
+        TimeoutObserver(state).next(value + 1);
+      }
+    }
+
+    fn error(&mut self, _: ()) { *self.errors.lock().unwrap() += 1; }
+
+    fn complete(&mut self) {}
+  }
+
+  #[test]
+  fn feed_source_from_downstream() {
+    let scheduler = TestScheduler::new();
+    let values = Arc::new(Mutex::new(vec![]));
+    let errors = Arc::new(Mutex::new(0));
+    let feedback = Arc::new(Mutex::new(None));
+    let on_timeout: fn(Feedback, SharedSubscription) =
+      |mut observer, _| observer.error(());
+    let state = Arc::new(Mutex::new(TimeoutState {
+      observer: Some(Feedback {
+        state: feedback.clone(),
+        values: values.clone(),
+        errors: errors.clone(),
+      }),
+      emitting: false,
+      queue: VecDeque::new(),
+      on_timeout: Some(on_timeout),
+      scheduler: scheduler.clone(),
+      dur: Duration::from_millis(10),
+      index: 0,
+      timer: None,
+      source: SharedSubscription::default(),
+      subscription: SharedSubscription::default(),
+    }));
+    *feedback.lock().unwrap() = Some(state.clone());
+
+    TimeoutObserver(state).next(0);
+    assert_eq!(*values.lock().unwrap(), vec![0, 1, 2, 3]);
+    scheduler.advance_by(Duration::from_millis(9));
+    assert_eq!(*errors.lock().unwrap(), 0);
+    scheduler.advance_by(Duration::from_millis(1));
+    assert_eq!(*errors.lock().unwrap(), 1);
+    feedback.lock().unwrap().take();
+  }
+
+  #[test]
+  fn local() {
+    let scheduler = LocalPoolScheduler::new();
+    let values = Rc::new(RefCell::new(vec![]));
+    let c_values = values.clone();
+    observable::create(|_| {})
+      .timeout_with_on(
+        Duration::from_millis(1),
+        observable::of(1),
+        scheduler.clone(),
+      )
+      .subscribe(move |v| c_values.borrow_mut().push(v));
+
+    scheduler.run();
+    assert_eq!(*values.borrow(), vec![1]);
+  }
+
+  #[test]
+  fn fork_and_shared() {
+    let t = observable::of(1).timeout(Duration::from_millis(10));
+    t.clone()
+      .timeout(Duration::from_millis(10))
+      .to_shared()
+      .subscribe_err(|_| {}, |_| {});
+    t.to_shared().subscribe_err(|_| {}, |_| {});
+    let w = observable::of(1)
+      .timeout_with(Duration::from_millis(10), observable::of(2));
+    w.clone()
+      .timeout_with(Duration::from_millis(10), observable::of(3))
+      .to_shared()
+      .subscribe(|_| {});
+    w.to_shared().subscribe(|_| {});
+  }
+}
@@ -0,0 +1,243 @@
+use crate::prelude::*;
+use observer::error_proxy_impl;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// An Observable that pairs every item of the source Observable with the
+/// latest item of another Observable.
+///
+/// This struct is created by the with_latest_from method on
+/// [Observable](Observable::with_latest_from). See its documentation for more.
+#[derive(Clone)]
+pub struct WithLatestFromOp<A, B> {
+  pub(crate) source: A,
+  pub(crate) other: B,
+}
+
+impl<A, B> Observable for WithLatestFromOp<A, B>
+where
+  A: Observable,
+  B: Observable<Err = A::Err>,
+{
+  type Item = (A::Item, B::Item);
+  type Err = A::Err;
+}
+
+impl<'a, A, B> LocalObservable<'a> for WithLatestFromOp<A, B>
+where
+  A: LocalObservable<'a>,
+  B: LocalObservable<'a, Err = A::Err>,
+  A::Item: 'a,
+  B::Item: Clone + 'a,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    subscriber: Subscriber<O, LocalSubscription>,
+  ) -> Self::Unsub {
+    let mut sub = subscriber.subscription;
+    let o_with = WithLatestFromObserver::new(subscriber.observer, sub.clone());
+    let o_with = Rc::new(RefCell::new(o_with));
+    // subscribe the other observable first, so its synchronous emissions are
+    // available to the source.
+    sub.add(self.other.actual_subscribe(Subscriber {
+      observer: OtherObserver(o_with.clone(), PhantomData),
+      subscription: LocalSubscription::default(),
+    }));
+
+    sub.add(self.source.actual_subscribe(Subscriber {
+      observer: SourceObserver(o_with, PhantomData),
+      subscription: LocalSubscription::default(),
+    }));
+    sub
+  }
+}
+
+impl<A, B> SharedObservable for WithLatestFromOp<A, B>
+where
+  A: SharedObservable,
+  B: SharedObservable<Err = A::Err>,
+  A::Item: Send + Sync + 'static,
+  B::Item: Clone + Send + Sync + 'static,
+  A::Unsub: Send + Sync,
+  B::Unsub: Send + Sync,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    let mut sub = subscriber.subscription;
+    let o_with = WithLatestFromObserver::new(subscriber.observer, sub.clone());
+    let o_with = Arc::new(Mutex::new(o_with));
+    sub.add(self.other.actual_subscribe(Subscriber {
+      observer: OtherObserver(o_with.clone(), PhantomData),
+      subscription: SharedSubscription::default(),
+    }));
+
+    sub.add(self.source.actual_subscribe(Subscriber {
+      observer: SourceObserver(o_with, PhantomData),
+      subscription: SharedSubscription::default(),
+    }));
+    sub
+  }
+}
+
+enum WithLatestItem<A, B> {
+  Source(A),
+  Other(B),
+}
+
+struct WithLatestFromObserver<O, U, B> {
+  observer: O,
+  subscription: U,
+  latest: Option<B>,
+}
+
+impl<O, U, B> WithLatestFromObserver<O, U, B> {
+  fn new(o: O, u: U) -> Self {
+    WithLatestFromObserver {
+      observer: o,
+      subscription: u,
+      latest: None,
+    }
+  }
+}
+
+impl<O, U, A, B, Err> Observer<WithLatestItem<A, B>, Err>
+  for WithLatestFromObserver<O, U, B>
+where
+  O: Observer<(A, B), Err>,
+  U: SubscriptionLike,
+  B: Clone,
+{
+  fn next(&mut self, value: WithLatestItem<A, B>) {
+    match value {
+      WithLatestItem::Source(v) => {
+        if let Some(latest) = &self.latest {
+          self.observer.next((v, latest.clone()));
+        }
+      }
+      WithLatestItem::Other(v) => self.latest = Some(v),
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    self.observer.error(err);
+    self.subscription.unsubscribe();
+  }
+
+  fn complete(&mut self) {
+    self.subscription.unsubscribe();
+    self.observer.complete();
+  }
+}
+
+struct SourceObserver<O, B>(O, PhantomData<B>);
+
+impl<O, A, B, Err> Observer<A, Err> for SourceObserver<O, B>
+where
+  O: Observer<WithLatestItem<A, B>, Err>,
+{
+  fn next(&mut self, value: A) { self.0.next(WithLatestItem::Source(value)); }
+
+  error_proxy_impl!(Err, 0);
+
+  fn complete(&mut self) { self.0.complete(); }
+}
+
+struct OtherObserver<O, A>(O, PhantomData<A>);
+
+impl<O, A, B, Err> Observer<B, Err> for OtherObserver<O, A>
+where
+  O: Observer<WithLatestItem<A, B>, Err>,
+{
+  fn next(&mut self, value: B) { self.0.next(WithLatestItem::Other(value)); }
+
+  error_proxy_impl!(Err, 0);
+
+  // The completion of the other observable only stops updating the latest
+  // item, the source keeps being paired with it.
+  fn complete(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::cell::Cell;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn smoke() {
+    let mut values = vec![];
+    let completed = Cell::new(false);
+    {
+      let mut source = Subject::new();
+      let mut other = Subject::new();
+      source
+        .clone()
+        .with_latest_from(other.clone())
+        .subscribe_complete(|v| values.push(v), || completed.set(true));
+
+      source.next(1);
+      other.next('a');
+      other.next('b');
+      source.next(2);
+      other.complete();
+      source.next(3);
+      assert!(!completed.get());
+      source.complete();
+    }
+    assert_eq!(values, vec![(2, 'b'), (3, 'b')]);
+    assert!(completed.get());
+  }
+
+  #[test]
+  fn error() {
+    let mut errors = 0;
+    {
+      let mut source = Subject::new();
+      let mut other = Subject::new();
+      source
+        .clone()
+        .with_latest_from(other.clone())
+        .subscribe_err(|_: (i32, i32)| {}, |_| errors += 1);
+
+      other.error("");
+      source.error("");
+    }
+    assert_eq!(errors, 1);
+  }
+
+  #[test]
+  fn synchronous_other() {
+    let mut values = vec![];
+    observable::from_iter(0..3)
+      .with_latest_from(observable::of("config"))
+      .subscribe(|v| values.push(v));
+
+    assert_eq!(values, vec![(0, "config"), (1, "config"), (2, "config")]);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut source = SharedSubject::new();
+    let mut other = SharedSubject::new();
+    source
+      .clone()
+      .with_latest_from(other.clone())
+      .to_shared()
+      .subscribe(move |v: (i32, i32)| c_values.lock().unwrap().push(v));
+
+    source.next(1);
+    other.next(10);
+    source.next(2);
+    assert_eq!(*values.lock().unwrap(), vec![(2, 10)]);
+  }
+}
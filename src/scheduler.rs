@@ -2,12 +2,25 @@ use crate::prelude::*;
 mod thread_scheduler;
 use thread_scheduler::new_thread_schedule;
 mod thread_pool_scheduler;
-use crate::observable::{from_future::DEFAULT_RUNTIME, interval::SpawnHandle};
-use futures::prelude::*;
-use futures::task::SpawnExt;
+mod current_thread_scheduler;
+use current_thread_scheduler::current_thread_schedule;
+mod test_scheduler;
+pub use test_scheduler::TestScheduler;
+mod local_scheduler;
+pub use local_scheduler::{LocalPoolScheduler, LocalScheduler};
+mod spawn_scheduler;
+pub use spawn_scheduler::SpawnScheduler;
+use spawn_scheduler::spawn_delay_task;
+mod runtime;
+pub(crate) use runtime::{spawn_default, spawn_default_with_handle};
+pub use runtime::{set_default_executor, set_default_pool_size};
+use crate::observable::interval::SpawnHandle;
 use futures_timer::Delay;
-use std::time::Duration;
-use thread_pool_scheduler::thread_pool_schedule;
+use runtime::with_default_runtime;
+use std::time::{Duration, Instant};
+use thread_pool_scheduler::{
+  thread_pool_schedule, thread_pool_schedule_periodic,
+};
 
 /// A Scheduler is an object to order task and schedule their execution.
 pub trait Scheduler {
@@ -17,12 +30,119 @@ pub trait Scheduler {
     delay: Option<Duration>,
     state: T,
   ) -> SharedSubscription;
+
+  /// Schedules `task` to run every `period`, starting `period` from now,
+  /// until the returned subscription is unsubscribed. `state` is passed to
+  /// each run of `task`.
+  ///
+  /// The default implementation reschedules `task` after each run, with a
+  /// delay computed from the scheduler clock so the runs don't drift. The
+  /// runs missed while the scheduler was busy are skipped, not run in a burst.
+  fn schedule_periodic<T: Send + 'static>(
+    &self,
+    period: Duration,
+    state: T,
+    task: impl FnMut(&mut T) + Send + 'static,
+  ) -> SharedSubscription
+  where
+    Self: Clone + Send + 'static,
+  {
+    self.schedule_periodic_at(self.now() + period, period, state, task)
+  }
+
+  /// Same as [`schedule_periodic`](Scheduler::schedule_periodic), but the
+  /// first run is at `at`.
+  fn schedule_periodic_at<T: Send + 'static>(
+    &self,
+    at: Instant,
+    period: Duration,
+    state: T,
+    task: impl FnMut(&mut T) + Send + 'static,
+  ) -> SharedSubscription
+  where
+    Self: Clone + Send + 'static,
+  {
+    let subscription = SharedSubscription::default();
+    schedule_period_tick(
+      self.clone(),
+      at,
+      period,
+      (state, task),
+      subscription.clone(),
+    );
+    subscription
+  }
+
+  /// The current time of the scheduler, time-based operators measure the
+  /// elapsed time with it.
+  #[inline]
+  fn now(&self) -> Instant { Instant::now() }
 }
 
+/// Schedules the run of the periodic task at `at`, which schedules the next
+/// run once it's done.
+fn schedule_period_tick<SD, T, F>(
+  scheduler: SD,
+  at: Instant,
+  period: Duration,
+  state: (T, F),
+  mut subscription: SharedSubscription,
+) where
+  SD: Scheduler + Clone + Send + 'static,
+  T: Send + 'static,
+  F: FnMut(&mut T) + Send + 'static,
+{
+  let delay = at.checked_duration_since(scheduler.now()).unwrap_or_default();
+  let c_scheduler = scheduler.clone();
+  let handle = scheduler.schedule(
+    move |tick, ((mut state, mut task), mut subscription)| {
+      subscription.remove(&tick);
+      if !subscription.is_closed() {
+        task(&mut state);
+        let next = next_period_tick(at, period, c_scheduler.now());
+        schedule_period_tick(
+          c_scheduler,
+          next,
+          period,
+          (state, task),
+          subscription,
+        );
+      }
+    },
+    Some(delay),
+    (state, subscription.clone()),
+  );
+  subscription.add(handle);
+}
+
+/// The first run after `now` of a periodic task last due at `at`, the missed
+/// ones are skipped.
+pub(crate) fn next_period_tick(
+  at: Instant,
+  period: Duration,
+  now: Instant,
+) -> Instant {
+  let next = at + period;
+  if next > now || period == Duration::default() {
+    next
+  } else {
+    let missed = now.duration_since(at).as_nanos() / period.as_nanos();
+    at + period * (missed as u32 + 1)
+  }
+}
+
+#[derive(Clone, Copy)]
 pub enum Schedulers {
   /// NewThread Scheduler always creates a new thread for each unit of work.
   NewThread,
-  /// ThreadPool dispatch task to the thread pool to execute task.
+  /// CurrentThread Scheduler runs the task on the current thread, after the
+  /// task of this scheduler currently running if any. Recursively scheduled
+  /// tasks are queued and run in order instead of nesting calls, and delayed
+  /// tasks block the thread until they are due.
+  CurrentThread,
+  /// ThreadPool dispatch task to the default runtime to execute task, a thread
+  /// pool unless configured by [`set_default_executor`] or
+  /// [`set_default_pool_size`].
   ThreadPool,
 }
 
@@ -35,35 +155,142 @@ impl Scheduler for Schedulers {
   ) -> SharedSubscription {
     match self {
       Schedulers::NewThread => new_thread_schedule(task, delay, state),
+      Schedulers::CurrentThread => current_thread_schedule(task, delay, state),
       Schedulers::ThreadPool => thread_pool_schedule(task, delay, state),
     }
   }
+
+  fn schedule_periodic_at<T: Send + 'static>(
+    &self,
+    at: Instant,
+    period: Duration,
+    state: T,
+    task: impl FnMut(&mut T) + Send + 'static,
+  ) -> SharedSubscription {
+    match self {
+      Schedulers::ThreadPool => {
+        thread_pool_schedule_periodic(at, period, state, task)
+      }
+      _ => {
+        let subscription = SharedSubscription::default();
+        schedule_period_tick(
+          *self,
+          at,
+          period,
+          (state, task),
+          subscription.clone(),
+        );
+        subscription
+      }
+    }
+  }
 }
 
 pub fn delay_task(
   delay: Duration,
   task: impl FnOnce() + Send + 'static,
 ) -> SpawnHandle<Result<(), std::io::Error>> {
-  let f = Delay::new(delay).inspect(|_| {
-    task();
-  });
-  let handle = DEFAULT_RUNTIME
-    .lock()
-    .unwrap()
-    .spawn_with_handle(f)
-    .expect("spawn task to thread pool failed.");
-  SpawnHandle::new(handle)
+  let delay = Delay::new(delay);
+  with_default_runtime(|spawner| spawn_delay_task(spawner, delay, task))
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
   extern crate test;
   use crate::prelude::*;
-  use crate::scheduler::Schedulers;
+  use crate::scheduler::{next_period_tick, Schedulers};
+  use std::cell::RefCell;
   use std::f32;
+  use std::rc::Rc;
   use std::sync::{Arc, Mutex};
+  use std::time::{Duration, Instant};
   use test::Bencher;
 
+  /// Schedules pushing `v` to `log`, `delay` milliseconds later.
+  pub(crate) fn schedule_push<SD: Scheduler>(
+    scheduler: &SD,
+    log: &Arc<Mutex<Vec<i32>>>,
+    v: i32,
+    delay: Option<u64>,
+  ) -> SharedSubscription {
+    let log = log.clone();
+    scheduler.schedule(
+      move |_, v| log.lock().unwrap().push(v),
+      delay.map(Duration::from_millis),
+      v,
+    )
+  }
+
+  /// Same as [`schedule_push`], on a [`LocalScheduler`].
+  pub(crate) fn local_schedule_push<SD: LocalScheduler>(
+    scheduler: &SD,
+    log: &Rc<RefCell<Vec<i32>>>,
+    v: i32,
+    delay: Option<u64>,
+  ) -> LocalSubscription {
+    let log = log.clone();
+    scheduler.schedule(
+      move |_, v| log.borrow_mut().push(v),
+      delay.map(Duration::from_millis),
+      v,
+    )
+  }
+
+  type LocalTask = Box<dyn FnOnce()>;
+
+  thread_local! {
+    static LOCAL_TASKS: RefCell<Vec<Option<LocalTask>>> = RefCell::new(vec![]);
+  }
+
+  /// A [`LocalScheduler`] in virtual time: its tasks are ordered and run by
+  /// the wrapped [`TestScheduler`], on the thread advancing it.
+  #[derive(Clone)]
+  pub(crate) struct LocalTestScheduler(pub(crate) TestScheduler);
+
+  impl LocalScheduler for LocalTestScheduler {
+    fn schedule<T: 'static>(
+      &self,
+      task: impl FnOnce(LocalSubscription, T) + 'static,
+      delay: Option<Duration>,
+      state: T,
+    ) -> LocalSubscription {
+      let mut subscription = LocalSubscription::default();
+      let c_subscription = subscription.clone();
+      // the task isn't `Send`, the test scheduler only carries its index.
+      let idx = LOCAL_TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        tasks.push(Some(Box::new(move || {
+          if !c_subscription.is_closed() {
+            task(c_subscription, state);
+          }
+        })));
+        tasks.len() - 1
+      });
+      let run = move |_, idx: usize| {
+        let task = LOCAL_TASKS.with(|tasks| tasks.borrow_mut()[idx].take());
+        if let Some(task) = task {
+          task();
+        }
+      };
+      subscription.add(self.0.schedule(run, delay, idx));
+      subscription
+    }
+
+    fn now(&self) -> Instant { self.0.now() }
+  }
+
+  #[test]
+  fn skip_missed_period_ticks() {
+    let at = std::time::Instant::now();
+    let period = Duration::from_millis(10);
+    assert_eq!(next_period_tick(at, period, at), at + period);
+    let late = at + Duration::from_millis(35);
+    assert_eq!(
+      next_period_tick(at, period, late),
+      at + Duration::from_millis(40)
+    );
+  }
+
   #[bench]
   fn pool(b: &mut Bencher) { b.iter(|| sum_of_sqrt(Schedulers::ThreadPool)) }
 
@@ -0,0 +1,178 @@
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct QueuedTask {
+  due: Instant,
+  sequence: usize,
+  subscription: SharedSubscription,
+  task: Box<dyn FnOnce()>,
+}
+
+#[derive(Default)]
+struct TaskQueue {
+  draining: bool,
+  sequence: usize,
+  tasks: Vec<QueuedTask>,
+}
+
+thread_local! {
+  static QUEUE: RefCell<TaskQueue> = RefCell::new(TaskQueue::default());
+}
+
+/// Runs `task` on the current thread. If a task of the current thread is
+/// already running, `task` is queued and run after it, so recursively
+/// scheduled work is executed in a loop instead of growing the stack.
+pub(crate) fn current_thread_schedule<T: Send + 'static>(
+  task: impl FnOnce(SharedSubscription, T) + Send + 'static,
+  delay: Option<Duration>,
+  state: T,
+) -> SharedSubscription {
+  let subscription = SharedSubscription::default();
+  let c_subscription = subscription.clone();
+  let task = Box::new(move || {
+    if !c_subscription.is_closed() {
+      task(c_subscription, state);
+    }
+  });
+  let drain = QUEUE.with(|queue| {
+    let mut queue = queue.borrow_mut();
+    let sequence = queue.sequence;
+    queue.sequence += 1;
+    queue.tasks.push(QueuedTask {
+      due: Instant::now() + delay.unwrap_or_default(),
+      sequence,
+      subscription: subscription.clone(),
+      task,
+    });
+    !std::mem::replace(&mut queue.draining, true)
+  });
+  if drain {
+    drain_queue();
+  }
+  subscription
+}
+
+/// Runs the queued tasks by due time, then by schedule order, until the queue
+/// is empty.
+fn drain_queue() {
+  // Resets the queue even if a task panics, so the thread can still schedule.
+  struct DrainGuard;
+  impl Drop for DrainGuard {
+    fn drop(&mut self) {
+      QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        queue.draining = false;
+        queue.tasks.clear();
+      });
+    }
+  }
+
+  let _guard = DrainGuard;
+  while let Some(QueuedTask { due, task, .. }) = QUEUE.with(|queue| {
+    let mut queue = queue.borrow_mut();
+    // cancelled tasks are dropped right away, not to wait for their delay.
+    queue.tasks.retain(|t| !t.subscription.is_closed());
+    let next = queue
+      .tasks
+      .iter()
+      .enumerate()
+      .min_by_key(|(_, t)| (t.due, t.sequence))
+      .map(|(idx, _)| idx);
+    next.map(|idx| queue.tasks.remove(idx))
+  }) {
+    let now = Instant::now();
+    if due > now {
+      thread::sleep(due - now);
+    }
+    task();
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use crate::scheduler::test::schedule_push;
+  use std::sync::{Arc, Mutex};
+  use std::time::{Duration, Instant};
+
+  #[test]
+  fn queue_nested_tasks() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let c_log = log.clone();
+    Schedulers::CurrentThread.schedule(
+      move |_, _| {
+        let inner_log = c_log.clone();
+        Schedulers::CurrentThread.schedule(
+          move |_, _| inner_log.lock().unwrap().push(2),
+          None,
+          (),
+        );
+        c_log.lock().unwrap().push(1);
+      },
+      None,
+      (),
+    );
+    assert_eq!(*log.lock().unwrap(), vec![1, 2]);
+  }
+
+  #[test]
+  fn delay_order() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let c_log = log.clone();
+    Schedulers::CurrentThread.schedule(
+      move |_, _| {
+        let push = |v, delay| {
+          schedule_push(&Schedulers::CurrentThread, &c_log, v, delay)
+        };
+        push(3, Some(10));
+        push(1, None);
+        push(4, Some(20)).unsubscribe();
+        push(2, Some(5));
+      },
+      None,
+      (),
+    );
+    assert_eq!(*log.lock().unwrap(), vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn cancelled_delay_does_not_block() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let c_log = log.clone();
+    let start = Instant::now();
+    Schedulers::CurrentThread.schedule(
+      move |_, _| {
+        let push = |v, delay| {
+          schedule_push(&Schedulers::CurrentThread, &c_log, v, delay)
+        };
+        push(1, Some(5));
+        push(2, Some(60_000)).unsubscribe();
+      },
+      None,
+      (),
+    );
+    assert_eq!(*log.lock().unwrap(), vec![1]);
+    assert!(start.elapsed() < Duration::from_secs(1));
+  }
+
+  #[test]
+  fn deep_recursion() {
+    fn count_down(n: usize, count: Arc<Mutex<usize>>) {
+      Schedulers::CurrentThread.schedule(
+        move |_, count: Arc<Mutex<usize>>| {
+          *count.lock().unwrap() += 1;
+          if n > 0 {
+            count_down(n - 1, count);
+          }
+        },
+        None,
+        count,
+      );
+    }
+    let count = Arc::new(Mutex::new(0));
+    count_down(1_000_000, count.clone());
+    assert_eq!(*count.lock().unwrap(), 1_000_001);
+  }
+}
@@ -0,0 +1,232 @@
+use crate::observable::interval::SpawnHandle;
+use crate::prelude::*;
+use crate::scheduler::next_period_tick;
+use futures::executor::{LocalPool, LocalSpawner};
+use futures::prelude::*;
+use futures::task::LocalSpawnExt;
+use futures_timer::Delay;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A Scheduler running its tasks on the current thread, so the tasks and the
+/// state they carry don't need to be `Send`.
+pub trait LocalScheduler {
+  fn schedule<T: 'static>(
+    &self,
+    task: impl FnOnce(LocalSubscription, T) + 'static,
+    delay: Option<Duration>,
+    state: T,
+  ) -> LocalSubscription;
+
+  /// Schedules `task` to run every `period`, starting `period` from now,
+  /// until the returned subscription is unsubscribed, like
+  /// [`Scheduler::schedule_periodic`].
+  fn schedule_periodic<T: 'static>(
+    &self,
+    period: Duration,
+    state: T,
+    task: impl FnMut(&mut T) + 'static,
+  ) -> LocalSubscription
+  where
+    Self: Clone + 'static,
+  {
+    self.schedule_periodic_at(self.now() + period, period, state, task)
+  }
+
+  /// Same as [`schedule_periodic`](LocalScheduler::schedule_periodic), but
+  /// the first run is at `at`.
+  fn schedule_periodic_at<T: 'static>(
+    &self,
+    at: Instant,
+    period: Duration,
+    state: T,
+    task: impl FnMut(&mut T) + 'static,
+  ) -> LocalSubscription
+  where
+    Self: Clone + 'static,
+  {
+    let subscription = LocalSubscription::default();
+    schedule_local_period_tick(
+      self.clone(),
+      at,
+      period,
+      (state, task),
+      subscription.clone(),
+    );
+    subscription
+  }
+
+  /// The current time of the scheduler, time-based operators measure the
+  /// elapsed time with it.
+  #[inline]
+  fn now(&self) -> Instant { Instant::now() }
+}
+
+/// Same as the periodic tick of [`Scheduler`], on a [`LocalScheduler`].
+fn schedule_local_period_tick<SD, T, F>(
+  scheduler: SD,
+  at: Instant,
+  period: Duration,
+  state: (T, F),
+  mut subscription: LocalSubscription,
+) where
+  SD: LocalScheduler + Clone + 'static,
+  T: 'static,
+  F: FnMut(&mut T) + 'static,
+{
+  let delay = at.checked_duration_since(scheduler.now()).unwrap_or_default();
+  let c_scheduler = scheduler.clone();
+  let handle = scheduler.schedule(
+    move |tick, ((mut state, mut task), mut subscription)| {
+      subscription.remove(&tick);
+      if !subscription.is_closed() {
+        task(&mut state);
+        let next = next_period_tick(at, period, c_scheduler.now());
+        schedule_local_period_tick(
+          c_scheduler,
+          next,
+          period,
+          (state, task),
+          subscription,
+        );
+      }
+    },
+    Some(delay),
+    (state, subscription.clone()),
+  );
+  subscription.add(handle);
+}
+
+/// A [`LocalScheduler`] backed by a `futures` `LocalPool`. The tasks are only
+/// run when the pool is run, by [`run`](LocalPoolScheduler::run) or
+/// [`run_until_stalled`](LocalPoolScheduler::run_until_stalled), on the thread
+/// calling it.
+///
+/// # Example
+///
+/// ```
+/// use rxrust::prelude::*;
+/// use std::rc::Rc;
+/// use std::time::Duration;
+///
+/// let scheduler = LocalPoolScheduler::new();
+/// let item = Rc::new(1);
+/// observable::of(item)
+///   .delay_on(Duration::from_millis(1), scheduler.clone())
+///   .subscribe(|v| println!("{}", v));
+///
+/// scheduler.run();
+/// ```
+#[derive(Clone)]
+pub struct LocalPoolScheduler {
+  pool: Rc<RefCell<LocalPool>>,
+  spawner: LocalSpawner,
+}
+
+impl Default for LocalPoolScheduler {
+  fn default() -> Self {
+    let pool = LocalPool::new();
+    let spawner = pool.spawner();
+    LocalPoolScheduler {
+      pool: Rc::new(RefCell::new(pool)),
+      spawner,
+    }
+  }
+}
+
+impl LocalPoolScheduler {
+  #[inline]
+  pub fn new() -> Self { Self::default() }
+
+  /// Runs the tasks until none of them is left, blocking the current thread
+  /// while waiting for the delayed ones.
+  ///
+  /// # Panics
+  ///
+  /// Panics if it's called by a task of this scheduler.
+  #[inline]
+  pub fn run(&self) { self.pool.borrow_mut().run() }
+
+  /// Runs the tasks until none of them can make progress without waiting.
+  ///
+  /// # Panics
+  ///
+  /// Panics if it's called by a task of this scheduler.
+  #[inline]
+  pub fn run_until_stalled(&self) { self.pool.borrow_mut().run_until_stalled() }
+}
+
+impl LocalScheduler for LocalPoolScheduler {
+  fn schedule<T: 'static>(
+    &self,
+    task: impl FnOnce(LocalSubscription, T) + 'static,
+    delay: Option<Duration>,
+    state: T,
+  ) -> LocalSubscription {
+    let mut subscription = LocalSubscription::default();
+    let c_subscription = subscription.clone();
+    let run = move || {
+      if !c_subscription.is_closed() {
+        task(c_subscription, state);
+      }
+    };
+    let f = match delay {
+      Some(delay) => Delay::new(delay).map(|_| run()).left_future(),
+      None => future::lazy(|_| run()).right_future(),
+    };
+    let handle = self
+      .spawner
+      .clone()
+      .spawn_local_with_handle(f)
+      .expect("spawn task to local pool failed.");
+    subscription.add(SpawnHandle::new(handle));
+    subscription
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use crate::scheduler::test::local_schedule_push;
+  use std::cell::RefCell;
+  use std::rc::Rc;
+  use std::time::Duration;
+
+  #[test]
+  fn run_on_current_thread() {
+    let scheduler = LocalPoolScheduler::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let push = |v, delay| local_schedule_push(&scheduler, &log, v, delay);
+    push(2, Some(10));
+    push(1, None);
+    push(3, Some(20)).unsubscribe();
+
+    assert!(log.borrow().is_empty());
+    scheduler.run();
+    assert_eq!(*log.borrow(), vec![1, 2]);
+  }
+
+  #[test]
+  fn periodic() {
+    let scheduler = LocalPoolScheduler::new();
+    let log = Rc::new(RefCell::new(vec![]));
+    let c_log = log.clone();
+    let mut subscription = LocalSubscription::default();
+    let mut c_subscription = subscription.clone();
+    subscription.add(scheduler.schedule_periodic(
+      Duration::from_millis(5),
+      0,
+      move |count| {
+        *count += 1;
+        c_log.borrow_mut().push(*count);
+        if *count == 3 {
+          c_subscription.unsubscribe();
+        }
+      },
+    ));
+
+    scheduler.run();
+    assert_eq!(*log.borrow(), vec![1, 2, 3]);
+  }
+}
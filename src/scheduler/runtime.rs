@@ -0,0 +1,88 @@
+use futures::executor::ThreadPool;
+use futures::future::{Future, RemoteHandle};
+use futures::task::{Spawn, SpawnExt};
+use std::io;
+use std::sync::Mutex;
+
+lazy_static! {
+  // Created on first use, so no thread pool is started if the application
+  // provides its own executor before.
+  static ref DEFAULT_RUNTIME: Mutex<Option<Box<dyn DefaultSpawn>>> =
+    Mutex::new(None);
+}
+
+// The executor behind the default runtime, cloned out of `DEFAULT_RUNTIME`
+// for each spawn, so the lock isn't held while the executor runs.
+trait DefaultSpawn: Send {
+  fn clone_spawn(&self) -> Box<dyn DefaultSpawn>;
+  fn as_spawn(&mut self) -> &mut dyn Spawn;
+}
+
+impl<S: Spawn + Clone + Send + 'static> DefaultSpawn for S {
+  fn clone_spawn(&self) -> Box<dyn DefaultSpawn> { Box::new(self.clone()) }
+  fn as_spawn(&mut self) -> &mut dyn Spawn { self }
+}
+
+/// Replaces the executor behind the default runtime, which runs
+/// `Schedulers::ThreadPool`, `observable::from_future`,
+/// `observable::interval` and the delayed tasks of `Schedulers::NewThread`.
+///
+/// The executor is cloned for each spawned task, it's usually a handle to the
+/// actual executor, like `futures::executor::ThreadPool`. The tasks already
+/// spawned keep running on the previous executor.
+pub fn set_default_executor(executor: impl Spawn + Clone + Send + 'static) {
+  *DEFAULT_RUNTIME.lock().unwrap() = Some(Box::new(executor));
+}
+
+/// Replaces the default runtime with a thread pool of `size` threads. By
+/// default, it's a thread pool with one thread per CPU.
+///
+/// Returns an `InvalidInput` error if `size` is 0, and the default runtime
+/// is left as is.
+pub fn set_default_pool_size(size: usize) -> io::Result<()> {
+  if size == 0 {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "the default pool size can't be 0.",
+    ));
+  }
+  let pool = ThreadPool::builder().pool_size(size).create()?;
+  set_default_executor(pool);
+  Ok(())
+}
+
+pub(crate) fn with_default_runtime<R>(
+  f: impl FnOnce(&mut dyn Spawn) -> R,
+) -> R {
+  let mut spawner = DEFAULT_RUNTIME
+    .lock()
+    .unwrap()
+    .get_or_insert_with(|| {
+      Box::new(ThreadPool::new().expect("create default thread pool failed."))
+    })
+    .clone_spawn();
+  f(spawner.as_spawn())
+}
+
+pub(crate) fn spawn_default(f: impl Future<Output = ()> + Send + 'static) {
+  with_default_runtime(|spawner| spawner.spawn(f))
+    .expect("spawn task to default runtime failed.");
+}
+
+pub(crate) fn spawn_default_with_handle(
+  f: impl Future<Output = ()> + Send + 'static,
+) -> RemoteHandle<()> {
+  with_default_runtime(|spawner| spawner.spawn_with_handle(f))
+    .expect("spawn task to default runtime failed.")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn zero_pool_size() {
+    let err = set_default_pool_size(0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+  }
+}
@@ -0,0 +1,117 @@
+use crate::observable::interval::SpawnHandle;
+use crate::prelude::*;
+use futures::prelude::*;
+use futures::task::{Spawn, SpawnExt};
+use futures_timer::Delay;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A Scheduler running its tasks on a user-supplied executor, any type
+/// implementing `futures::task::Spawn`, so the tasks of rxrust are run by the
+/// executor of the application.
+///
+/// # Example
+///
+/// ```
+/// use futures::executor::ThreadPool;
+/// use rxrust::prelude::*;
+/// use std::time::Duration;
+///
+/// let pool = ThreadPool::new().unwrap();
+/// let scheduler = SpawnScheduler::new(pool);
+/// observable::interval_on(Duration::from_millis(10), scheduler.clone())
+///   .to_shared()
+///   .observe_on(scheduler)
+///   .to_shared()
+///   .subscribe(|v| println!("{}", v));
+/// ```
+pub struct SpawnScheduler<S>(Arc<Mutex<S>>);
+
+impl<S: Spawn> SpawnScheduler<S> {
+  #[inline]
+  pub fn new(spawner: S) -> Self {
+    SpawnScheduler(Arc::new(Mutex::new(spawner)))
+  }
+}
+
+impl<S> Clone for SpawnScheduler<S> {
+  #[inline]
+  fn clone(&self) -> Self { SpawnScheduler(self.0.clone()) }
+}
+
+impl<S: Spawn + Send> Scheduler for SpawnScheduler<S> {
+  fn schedule<T: Send + 'static>(
+    &self,
+    task: impl FnOnce(SharedSubscription, T) + Send + 'static,
+    delay: Option<Duration>,
+    state: T,
+  ) -> SharedSubscription {
+    let mut subscription = SharedSubscription::default();
+    let c_subscription = subscription.clone();
+    // started before waiting for the lock, so the task isn't late.
+    let delay = Delay::new(delay.unwrap_or_default());
+    let handle = spawn_delay_task(
+      &mut *self.0.lock().unwrap(),
+      delay,
+      move || task(c_subscription, state),
+    );
+    subscription.add(handle);
+    subscription
+  }
+}
+
+/// Spawns `task` on `spawner`, to run once `delay` is elapsed.
+pub(crate) fn spawn_delay_task<Sp: Spawn + ?Sized>(
+  spawner: &mut Sp,
+  delay: Delay,
+  task: impl FnOnce() + Send + 'static,
+) -> SpawnHandle<Result<(), std::io::Error>> {
+  let f = delay.inspect(|_| {
+    task();
+  });
+  let handle = spawner
+    .spawn_with_handle(f)
+    .expect("spawn task to executor failed.");
+  SpawnHandle::new(handle)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use futures::executor::ThreadPool;
+  use std::sync::{mpsc, Arc, Mutex};
+  use std::thread;
+  use std::time::Duration;
+
+  #[test]
+  fn run_on_executor() {
+    let pool = ThreadPool::builder()
+      .pool_size(1)
+      .name_prefix("spawn-scheduler-")
+      .create()
+      .unwrap();
+    let scheduler = SpawnScheduler::new(pool);
+    let ticks = Arc::new(Mutex::new(vec![]));
+    let c_ticks = ticks.clone();
+    let (done, finished) = mpsc::channel();
+
+    observable::interval_on(Duration::from_millis(1), scheduler)
+      .take(3)
+      .to_shared()
+      .subscribe_complete(
+        move |v| {
+          let name = thread::current().name().map(str::to_owned);
+          c_ticks.lock().unwrap().push((v, name));
+        },
+        move || done.send(()).unwrap(),
+      );
+
+    // wait for the completion instead of guessing how long the ticks take.
+    finished.recv().unwrap();
+    let name = Some("spawn-scheduler-0".to_owned());
+    assert_eq!(
+      *ticks.lock().unwrap(),
+      vec![(0, name.clone()), (1, name.clone()), (2, name)]
+    );
+  }
+}
@@ -0,0 +1,229 @@
+use crate::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A Scheduler working in virtual time, for deterministic tests of time-based
+/// operators.
+///
+/// Its clock starts at zero when it's created, and only advances when
+/// [`advance_by`](TestScheduler::advance_by) or
+/// [`advance_to`](TestScheduler::advance_to) is called, which run all the
+/// tasks due until then, in order. Tasks scheduled without delay are run at
+/// the next advance.
+///
+/// # Example
+///
+/// ```
+/// use rxrust::prelude::*;
+/// use std::sync::{Arc, Mutex};
+/// use std::time::Duration;
+///
+/// let scheduler = TestScheduler::new();
+/// let ticks = Arc::new(Mutex::new(vec![]));
+/// let c_ticks = ticks.clone();
+/// observable::interval_on(Duration::from_millis(10), scheduler.clone())
+///   .to_shared()
+///   .subscribe(move |v| c_ticks.lock().unwrap().push(v));
+///
+/// scheduler.advance_by(Duration::from_millis(35));
+/// assert_eq!(*ticks.lock().unwrap(), vec![0, 1, 2]);
+/// ```
+#[derive(Clone, Default)]
+pub struct TestScheduler(Arc<Mutex<InnerTestScheduler>>);
+
+struct InnerTestScheduler {
+  start: Instant,
+  elapsed: Duration,
+  sequence: usize,
+  tasks: Vec<ScheduledTask>,
+}
+
+struct ScheduledTask {
+  due: Duration,
+  sequence: usize,
+  task: Box<dyn FnOnce() + Send>,
+}
+
+impl Default for InnerTestScheduler {
+  fn default() -> Self {
+    InnerTestScheduler {
+      start: Instant::now(),
+      elapsed: Duration::default(),
+      sequence: 0,
+      tasks: vec![],
+    }
+  }
+}
+
+impl TestScheduler {
+  #[inline]
+  pub fn new() -> Self { Self::default() }
+
+  /// The virtual time elapsed since the scheduler was created.
+  pub fn elapsed(&self) -> Duration { self.0.lock().unwrap().elapsed }
+
+  /// The count of tasks waiting to be run.
+  pub fn pending_size(&self) -> usize { self.0.lock().unwrap().tasks.len() }
+
+  /// Advances the virtual clock by `dur`, see
+  /// [`advance_to`](TestScheduler::advance_to).
+  pub fn advance_by(&self, dur: Duration) {
+    let to = self.elapsed() + dur;
+    self.advance_to(to);
+  }
+
+  /// Advances the virtual clock to `elapsed` since the scheduler was created,
+  /// and runs all the tasks due until then, including those they schedule.
+  /// The clock never goes back, so an `elapsed` in the past only runs the
+  /// tasks already due.
+  pub fn advance_to(&self, elapsed: Duration) {
+    loop {
+      let mut inner = self.0.lock().unwrap();
+      let next = inner
+        .tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.due <= elapsed)
+        .min_by_key(|(_, t)| (t.due, t.sequence))
+        .map(|(idx, _)| idx);
+      match next {
+        Some(idx) => {
+          let ScheduledTask { due, task, .. } = inner.tasks.remove(idx);
+          if due > inner.elapsed {
+            inner.elapsed = due;
+          }
+          // release the lock, the task may schedule new tasks.
+          drop(inner);
+          task();
+        }
+        None => {
+          if elapsed > inner.elapsed {
+            inner.elapsed = elapsed;
+          }
+          break;
+        }
+      }
+    }
+  }
+
+  /// Runs all the tasks already due, without advancing the clock.
+  #[inline]
+  pub fn flush(&self) {
+    let now = self.elapsed();
+    self.advance_to(now);
+  }
+}
+
+impl Scheduler for TestScheduler {
+  fn schedule<T: Send + 'static>(
+    &self,
+    task: impl FnOnce(SharedSubscription, T) + Send + 'static,
+    delay: Option<Duration>,
+    state: T,
+  ) -> SharedSubscription {
+    let subscription = SharedSubscription::default();
+    let c_subscription = subscription.clone();
+    let mut inner = self.0.lock().unwrap();
+    let due = inner.elapsed + delay.unwrap_or_default();
+    let sequence = inner.sequence;
+    inner.sequence += 1;
+    inner.tasks.push(ScheduledTask {
+      due,
+      sequence,
+      task: Box::new(move || {
+        if !c_subscription.is_closed() {
+          task(c_subscription, state);
+        }
+      }),
+    });
+    subscription
+  }
+
+  fn now(&self) -> Instant {
+    let inner = self.0.lock().unwrap();
+    inner.start + inner.elapsed
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use crate::scheduler::test::schedule_push;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn run_in_virtual_time_order() {
+    let scheduler = TestScheduler::new();
+    let log = Arc::new(Mutex::new(vec![]));
+    let push = |v, delay| schedule_push(&scheduler, &log, v, delay);
+    push(3, Some(30));
+    push(1, Some(10));
+    push(0, None);
+    push(2, Some(10));
+    push(4, Some(40)).unsubscribe();
+
+    assert!(log.lock().unwrap().is_empty());
+    scheduler.advance_by(Duration::from_millis(10));
+    assert_eq!(*log.lock().unwrap(), vec![0, 1, 2]);
+    assert_eq!(scheduler.elapsed(), Duration::from_millis(10));
+
+    scheduler.advance_to(Duration::from_millis(50));
+    assert_eq!(*log.lock().unwrap(), vec![0, 1, 2, 3]);
+    assert_eq!(scheduler.pending_size(), 0);
+  }
+
+  #[test]
+  fn nested_schedule() {
+    let scheduler = TestScheduler::new();
+    let log = Arc::new(Mutex::new(vec![]));
+    let c_log = log.clone();
+    let c_scheduler = scheduler.clone();
+    scheduler.schedule(
+      move |_, _| {
+        let log = c_log.clone();
+        c_log.lock().unwrap().push(c_scheduler.elapsed());
+        let inner = c_scheduler.clone();
+        c_scheduler.schedule(
+          move |_, _| log.lock().unwrap().push(inner.elapsed()),
+          Some(Duration::from_millis(5)),
+          (),
+        );
+      },
+      Some(Duration::from_millis(5)),
+      (),
+    );
+
+    scheduler.advance_by(Duration::from_millis(20));
+    assert_eq!(
+      *log.lock().unwrap(),
+      vec![Duration::from_millis(5), Duration::from_millis(10)]
+    );
+    assert_eq!(scheduler.elapsed(), Duration::from_millis(20));
+  }
+
+  #[test]
+  fn periodic() {
+    let scheduler = TestScheduler::new();
+    let log = Arc::new(Mutex::new(vec![]));
+    let c_log = log.clone();
+    let c_scheduler = scheduler.clone();
+    let mut subscription = scheduler.schedule_periodic(
+      Duration::from_millis(10),
+      0,
+      move |count| {
+        *count += 1;
+        c_log.lock().unwrap().push((*count, c_scheduler.elapsed()));
+      },
+    );
+
+    scheduler.advance_by(Duration::from_millis(25));
+    assert_eq!(
+      *log.lock().unwrap(),
+      vec![(1, Duration::from_millis(10)), (2, Duration::from_millis(20))]
+    );
+    subscription.unsubscribe();
+    scheduler.advance_by(Duration::from_millis(25));
+    assert_eq!(log.lock().unwrap().len(), 2);
+  }
+}
@@ -1,5 +1,9 @@
+use crate::observable::interval::SpawnHandle;
 use crate::prelude::*;
-use std::time::Duration;
+use crate::scheduler::spawn_default_with_handle;
+use futures::prelude::*;
+use futures_timer::Interval;
+use std::time::{Duration, Instant};
 
 pub(crate) fn thread_pool_schedule<T: Send + 'static>(
   task: impl FnOnce(SharedSubscription, T) + Send + 'static,
@@ -14,3 +18,20 @@ pub(crate) fn thread_pool_schedule<T: Send + 'static>(
   subscription.add(s);
   subscription
 }
+
+/// Runs `task` every `period` from `at`, in a single task of the default
+/// runtime woken by a timer `Interval`, rather than a task per run.
+pub(crate) fn thread_pool_schedule_periodic<T: Send + 'static>(
+  at: Instant,
+  period: Duration,
+  mut state: T,
+  mut task: impl FnMut(&mut T) + Send + 'static,
+) -> SharedSubscription {
+  let mut subscription = SharedSubscription::default();
+  let f = Interval::new_at(at, period).for_each(move |_| {
+    task(&mut state);
+    future::ready(())
+  });
+  subscription.add(SpawnHandle::new(spawn_default_with_handle(f)));
+  subscription
+}
@@ -11,6 +11,15 @@ pub use local_subject::*;
 mod shared_subject;
 pub use shared_subject::*;
 
+mod behavior_subject;
+pub use behavior_subject::*;
+
+mod replay_subject;
+pub use replay_subject::*;
+
+mod async_subject;
+pub use async_subject::*;
+
 #[derive(Default, Clone)]
 pub struct Subject<O, S> {
   pub(crate) observers: O,
@@ -29,6 +38,83 @@ subscription_proxy_impl!(Subject<O, U>, {subscription}, U, <O>);
 observer_proxy_impl!(Subject<O, U>, {observers}, Item, Err, O, <U>, 
   {where Item: PayloadCopy, Err: PayloadCopy});
 
+/// The terminal notification a subject received.
+pub(crate) enum Terminal<Err> {
+  // the error is kept with its copy function, so replaying it to late
+  // subscribers doesn't require `Err: PayloadCopy` to subscribe.
+  Error(Err, fn(&Err) -> Err),
+  Complete,
+}
+
+impl<Err> Terminal<Err> {
+  pub(crate) fn error(err: Err) -> Self
+  where
+    Err: PayloadCopy,
+  {
+    Terminal::Error(err, Err::payload_copy)
+  }
+
+  pub(crate) fn payload_copy(&self) -> Self {
+    match self {
+      Terminal::Error(err, copy) => Terminal::Error(copy(err), *copy),
+      Terminal::Complete => Terminal::Complete,
+    }
+  }
+
+  pub(crate) fn notify<Item, O>(self, observer: &mut O)
+  where
+    O: Observer<Item, Err>,
+  {
+    match self {
+      Terminal::Error(err, _) => observer.error(err),
+      Terminal::Complete => observer.complete(),
+    }
+  }
+}
+
+/// The observers subscribed to a subject, and the terminal notification the
+/// subject received, so observers subscribing after it can be notified.
+pub struct Publishers<P, Err> {
+  pub(crate) list: Vec<P>,
+  pub(crate) terminal: Option<Terminal<Err>>,
+}
+
+impl<P, Err> Default for Publishers<P, Err> {
+  fn default() -> Self {
+    Publishers {
+      list: vec![],
+      terminal: None,
+    }
+  }
+}
+
+impl<Item, Err, P> Observer<Item, Err> for Publishers<P, Err>
+where
+  P: Publisher<Item, Err>,
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  fn next(&mut self, value: Item) {
+    if self.terminal.is_none() {
+      self.list.next(value);
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    if self.terminal.is_none() {
+      self.terminal = Some(Terminal::error(err.payload_copy()));
+      self.list.error(err);
+    }
+  }
+
+  fn complete(&mut self) {
+    if self.terminal.is_none() {
+      self.terminal = Some(Terminal::Complete);
+      self.list.complete();
+    }
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -91,6 +177,43 @@ mod test {
     assert_eq!(*c_v.lock().unwrap(), 100);
   }
 
+  #[test]
+  fn notify_late_subscriber() {
+    let mut completed = false;
+    let mut error = None;
+    {
+      let mut subject = Subject::new();
+      subject.next(1);
+      subject.complete();
+      subject
+        .clone()
+        .subscribe_complete(|_: i32| {}, || completed = true);
+      assert_eq!(subject.subscribed_size(), 0);
+
+      let mut subject = Subject::new();
+      subject.error("error");
+      subject
+        .clone()
+        .subscribe_err(|_: i32| {}, |e| error = Some(e));
+    }
+    assert!(completed);
+    assert_eq!(error, Some("error"));
+  }
+
+  #[test]
+  fn shared_notify_late_subscriber() {
+    use std::sync::{Arc, Mutex};
+    let completed = Arc::new(Mutex::new(false));
+    let c_completed = completed.clone();
+    let mut subject = SharedSubject::new();
+    subject.complete();
+    subject.clone().to_shared().subscribe_complete(
+      |_: i32| {},
+      move || *c_completed.lock().unwrap() = true,
+    );
+    assert!(*completed.lock().unwrap());
+  }
+
   #[test]
   fn subject_subscribe_subject() {
     let mut local = LocalSubject::new();
@@ -0,0 +1,305 @@
+use super::{LocalPublishers, SharedPublishers, Terminal};
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// A Subject that only emits the last item it received, and only once it
+/// completes. Subscribers arriving after completion receive the last item and
+/// the completion immediately. If it errors, only the error is emitted.
+#[derive(Clone)]
+pub struct AsyncSubject<O, S, V> {
+  pub(crate) subject: Subject<O, S>,
+  pub(crate) state: V,
+}
+
+pub type LocalAsyncSubject<'a, Item, Err> = AsyncSubject<
+  LocalPublishers<'a, Item, Err>,
+  LocalSubscription,
+  Rc<RefCell<AsyncState<Item, Err>>>,
+>;
+
+pub type SharedAsyncSubject<Item, Err> = AsyncSubject<
+  SharedPublishers<Item, Err>,
+  SharedSubscription,
+  Arc<Mutex<AsyncState<Item, Err>>>,
+>;
+
+subscription_proxy_impl!(AsyncSubject<O, U, V>, {subject}, U, <O, V>);
+
+#[doc(hidden)]
+pub struct AsyncState<Item, Err> {
+  value: Option<Item>,
+  terminal: Option<Terminal<Err>>,
+}
+
+impl<Item, Err> Default for AsyncState<Item, Err> {
+  fn default() -> Self {
+    AsyncState {
+      value: None,
+      terminal: None,
+    }
+  }
+}
+
+impl<Item, Err> AsyncState<Item, Err> {
+  /// Copies the last item and terminal notification out of the state, returns
+  /// `None` if the subject is not terminated yet.
+  fn snapshot(&self) -> Option<(Option<Item>, Terminal<Err>)>
+  where
+    Item: PayloadCopy,
+    Err: PayloadCopy,
+  {
+    self.terminal.as_ref().map(|t| match t {
+      Terminal::Error(..) => (None, t.payload_copy()),
+      Terminal::Complete => {
+        (self.value.as_ref().map(|v| v.payload_copy()), Terminal::Complete)
+      }
+    })
+  }
+}
+
+fn emit_snapshot<Item, Err, O>(
+  observer: &mut O,
+  (value, terminal): (Option<Item>, Terminal<Err>),
+) where
+  O: Observer<Item, Err>,
+{
+  if let Some(value) = value {
+    observer.next(value);
+  }
+  terminal.notify(observer);
+}
+
+impl<'a, Item, Err> LocalAsyncSubject<'a, Item, Err> {
+  #[inline]
+  pub fn new() -> Self {
+    AsyncSubject {
+      subject: Subject::new(),
+      state: Default::default(),
+    }
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<Item, Err> SharedAsyncSubject<Item, Err> {
+  #[inline]
+  pub fn new() -> Self {
+    AsyncSubject {
+      subject: Subject::new(),
+      state: Default::default(),
+    }
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<'a, Item, Err> Default for LocalAsyncSubject<'a, Item, Err> {
+  #[inline]
+  fn default() -> Self { Self::new() }
+}
+
+impl<Item, Err> Default for SharedAsyncSubject<Item, Err> {
+  #[inline]
+  fn default() -> Self { Self::new() }
+}
+
+impl<'a, Item, Err> Observable for LocalAsyncSubject<'a, Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<Item, Err> Observable for SharedAsyncSubject<Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+#[doc(hidden)]
+macro observer_impl(
+  $item: ident, $err: ident, $($lock: tt $($parentheses: tt)?).+
+) {
+  fn next(&mut self, value: $item) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    if state.terminal.is_none() {
+      state.value = Some(value);
+    }
+  }
+
+  fn error(&mut self, err: $err) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    if state.terminal.is_none() {
+      state.value = None;
+      state.terminal = Some(Terminal::error(err.payload_copy()));
+      drop(state);
+      self.subject.error(err);
+    }
+  }
+
+  fn complete(&mut self) {
+    let mut state = self.state.$($lock$($parentheses)?).+;
+    if state.terminal.is_none() {
+      state.terminal = Some(Terminal::Complete);
+      let snapshot = state.snapshot();
+      drop(state);
+      if let Some(snapshot) = snapshot {
+        emit_snapshot(&mut self.subject, snapshot);
+      }
+    }
+  }
+}
+
+impl<'a, Item, Err> Observer<Item, Err> for LocalAsyncSubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  observer_impl!(Item, Err, borrow_mut());
+}
+
+impl<Item, Err> Observer<Item, Err> for SharedAsyncSubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  observer_impl!(Item, Err, lock().unwrap());
+}
+
+/// Emits the snapshot if the subject terminated, registers the subscriber
+/// otherwise. The state is held while registering, so a completion from
+/// another thread can't be missed.
+#[doc(hidden)]
+macro subscribe_impl(
+  $subject: ident, $subscriber: ident, $($lock: tt $($parentheses: tt)?).+
+) {{
+  let state = $subject.state.$($lock$($parentheses)?).+;
+  match state.snapshot() {
+    Some(snapshot) => {
+      drop(state);
+      emit_snapshot(&mut $subscriber, snapshot);
+      $subscriber.subscription
+    }
+    None => $subject.subject.actual_subscribe($subscriber),
+  }
+}}
+
+impl<'a, Item, Err> LocalObservable<'a> for LocalAsyncSubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    mut subscriber: Subscriber<O, LocalSubscription>,
+  ) -> LocalSubscription {
+    subscribe_impl!(self, subscriber, borrow())
+  }
+}
+
+impl<Item, Err> SharedObservable for SharedAsyncSubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    mut subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    subscribe_impl!(self, subscriber, lock().unwrap())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::cell::{Cell, RefCell};
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn emit_last_on_complete() {
+    let mut early = vec![];
+    let mut late = vec![];
+    let completed = Cell::new(0);
+    {
+      let mut subject = LocalAsyncSubject::new();
+      subject
+        .clone()
+        .subscribe_complete(|v| early.push(v), || {
+          completed.set(completed.get() + 1)
+        });
+      subject.next(1);
+      subject.next(2);
+      subject.complete();
+      subject.next(3);
+      subject
+        .clone()
+        .subscribe_complete(|v| late.push(v), || {
+          completed.set(completed.get() + 1)
+        });
+    }
+    assert_eq!(early, vec![2]);
+    assert_eq!(late, vec![2]);
+    assert_eq!(completed.get(), 2);
+  }
+
+  #[test]
+  fn empty_complete() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut subject = LocalAsyncSubject::new();
+      subject.complete();
+      subject
+        .clone()
+        .subscribe_complete(|v: i32| values.push(v), || completed = true);
+    }
+    assert!(values.is_empty());
+    assert!(completed);
+  }
+
+  #[test]
+  fn error() {
+    let values = RefCell::new(vec![]);
+    let errors = Cell::new(0);
+    {
+      let mut subject = LocalAsyncSubject::new();
+      subject
+        .clone()
+        .subscribe_err(
+          |v: i32| values.borrow_mut().push(v),
+          |_| errors.set(errors.get() + 1),
+        );
+      subject.next(1);
+      subject.error("");
+      subject
+        .clone()
+        .subscribe_err(
+          |v: i32| values.borrow_mut().push(v),
+          |_| errors.set(errors.get() + 1),
+        );
+    }
+    assert!(values.borrow().is_empty());
+    assert_eq!(errors.get(), 2);
+  }
+
+  #[test]
+  fn shared() {
+    let value = Arc::new(Mutex::new(0));
+    let c_value = value.clone();
+    let mut subject = SharedAsyncSubject::new();
+    subject.next(1);
+    subject.complete();
+    subject
+      .clone()
+      .to_shared()
+      .subscribe(move |v: i32| *c_value.lock().unwrap() = v);
+
+    assert_eq!(*value.lock().unwrap(), 1);
+  }
+}
@@ -0,0 +1,241 @@
+use super::{LocalPublishers, SharedPublishers};
+use crate::prelude::*;
+use observer::{complete_proxy_impl, error_proxy_impl};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// A Subject that holds a current value. Every new subscriber receives the
+/// current value immediately, and then all the values emitted after it
+/// subscribed. Once the subject completed or emitted an error, new
+/// subscribers only receive that notification.
+#[derive(Clone)]
+pub struct BehaviorSubject<O, S, V> {
+  pub(crate) subject: Subject<O, S>,
+  pub(crate) value: V,
+}
+
+pub type LocalBehaviorSubject<'a, Item, Err> = BehaviorSubject<
+  LocalPublishers<'a, Item, Err>,
+  LocalSubscription,
+  Rc<RefCell<Item>>,
+>;
+
+pub type SharedBehaviorSubject<Item, Err> = BehaviorSubject<
+  SharedPublishers<Item, Err>,
+  SharedSubscription,
+  Arc<Mutex<Item>>,
+>;
+
+subscription_proxy_impl!(BehaviorSubject<O, U, V>, {subject}, U, <O, V>);
+
+impl<'a, Item, Err> LocalBehaviorSubject<'a, Item, Err> {
+  #[inline]
+  pub fn new(value: Item) -> Self {
+    BehaviorSubject {
+      subject: Subject::new(),
+      value: Rc::new(RefCell::new(value)),
+    }
+  }
+
+  /// Returns a copy of the current value.
+  #[inline]
+  pub fn value(&self) -> Item
+  where
+    Item: PayloadCopy,
+  {
+    self.value.borrow().payload_copy()
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<Item, Err> SharedBehaviorSubject<Item, Err> {
+  #[inline]
+  pub fn new(value: Item) -> Self {
+    BehaviorSubject {
+      subject: Subject::new(),
+      value: Arc::new(Mutex::new(value)),
+    }
+  }
+
+  /// Returns a copy of the current value.
+  #[inline]
+  pub fn value(&self) -> Item
+  where
+    Item: PayloadCopy,
+  {
+    self.value.lock().unwrap().payload_copy()
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<'a, Item, Err> Observable for LocalBehaviorSubject<'a, Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<Item, Err> Observable for SharedBehaviorSubject<Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<'a, Item, Err> LocalObservable<'a> for LocalBehaviorSubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    mut subscriber: Subscriber<O, LocalSubscription>,
+  ) -> LocalSubscription {
+    // copy the value out before emitting, the subscriber may emit a new
+    // value to this subject.
+    let value = self.value.borrow().payload_copy();
+    if self.subject.observers.borrow().terminal.is_none() {
+      subscriber.next(value);
+    }
+    self.subject.actual_subscribe(subscriber)
+  }
+}
+
+impl<Item, Err> SharedObservable for SharedBehaviorSubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    mut subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    // copy the value out before emitting, the subscriber may read or emit a
+    // new value to this subject.
+    let value = self.value.lock().unwrap().payload_copy();
+    if self.subject.observers.lock().unwrap().terminal.is_none() {
+      subscriber.next(value);
+    }
+    self.subject.actual_subscribe(subscriber)
+  }
+}
+
+impl<'a, Item, Err> Observer<Item, Err> for LocalBehaviorSubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  fn next(&mut self, value: Item) {
+    *self.value.borrow_mut() = value.payload_copy();
+    self.subject.next(value);
+  }
+
+  error_proxy_impl!(Err, subject);
+  complete_proxy_impl!(subject);
+}
+
+impl<Item, Err> Observer<Item, Err> for SharedBehaviorSubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  fn next(&mut self, value: Item) {
+    *self.value.lock().unwrap() = value.payload_copy();
+    self.subject.next(value);
+  }
+
+  error_proxy_impl!(Err, subject);
+  complete_proxy_impl!(subject);
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn replay_current_value() {
+    let mut first = vec![];
+    let mut second = vec![];
+    {
+      let mut subject = LocalBehaviorSubject::new(0);
+      subject.clone().subscribe(|v| first.push(v));
+      subject.next(1);
+      subject.clone().subscribe(|v| second.push(v));
+      subject.next(2);
+
+      assert_eq!(subject.value(), 2);
+      assert_eq!(subject.subscribed_size(), 2);
+    }
+    assert_eq!(first, vec![0, 1, 2]);
+    assert_eq!(second, vec![1, 2]);
+  }
+
+  #[test]
+  fn emit_in_subscriber() {
+    let mut values = vec![];
+    {
+      let mut subject = LocalBehaviorSubject::new(0);
+      let mut c_subject = subject.clone();
+      subject.clone().subscribe(move |v| {
+        if v == 0 {
+          c_subject.next(1);
+        }
+      });
+      subject.clone().subscribe(|v| values.push(v));
+      subject.next(2);
+    }
+    assert_eq!(values, vec![1, 2]);
+  }
+
+  #[test]
+  fn only_terminal_after_complete() {
+    let mut values = vec![];
+    let mut completed = false;
+    {
+      let mut subject = LocalBehaviorSubject::<_, ()>::new(0);
+      subject.next(1);
+      subject.complete();
+      subject
+        .clone()
+        .subscribe_complete(|v| values.push(v), || completed = true);
+    }
+    assert!(values.is_empty());
+    assert!(completed);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut subject = SharedBehaviorSubject::new(0);
+    subject.next(1);
+    subject
+      .clone()
+      .to_shared()
+      .subscribe(move |v: i32| c_values.lock().unwrap().push(v));
+    subject.next(2);
+
+    assert_eq!(*values.lock().unwrap(), vec![1, 2]);
+    assert_eq!(subject.value(), 2);
+  }
+
+  #[test]
+  fn shared_read_value_in_subscriber() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut subject = SharedBehaviorSubject::<_, ()>::new(0);
+    let c_subject = subject.clone();
+    subject.clone().to_shared().subscribe(move |v: i32| {
+      c_values.lock().unwrap().push((v, c_subject.value()));
+    });
+    subject.next(1);
+
+    assert_eq!(*values.lock().unwrap(), vec![(0, 0), (1, 1)]);
+  }
+}
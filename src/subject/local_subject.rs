@@ -1,9 +1,10 @@
+use super::{Publishers, Terminal};
 use crate::prelude::*;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-type LocalPublishers<'a, Item, Err> =
-  Rc<RefCell<Vec<Box<dyn Publisher<Item, Err> + 'a>>>>;
+pub(crate) type LocalPublishers<'a, Item, Err> =
+  Rc<RefCell<Publishers<Box<dyn Publisher<Item, Err> + 'a>, Err>>>;
 
 pub type LocalSubject<'a, Item, Err> =
   Subject<LocalPublishers<'a, Item, Err>, LocalSubscription>;
@@ -16,17 +17,27 @@ impl<'a, Item, Err> LocalObservable<'a> for LocalSubject<'a, Item, Err> {
   type Unsub = LocalSubscription;
   fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
     mut self,
-    subscriber: Subscriber<O, LocalSubscription>,
+    mut subscriber: Subscriber<O, LocalSubscription>,
   ) -> LocalSubscription {
     let subscription = subscriber.subscription.clone();
-    self.subscription.add(subscription.clone());
-    self.observers.borrow_mut().push(Box::new(subscriber));
+    let mut observers = self.observers.borrow_mut();
+    match observers.terminal.as_ref().map(Terminal::payload_copy) {
+      Some(terminal) => {
+        drop(observers);
+        terminal.notify(&mut subscriber);
+      }
+      None => {
+        observers.list.push(Box::new(subscriber));
+        drop(observers);
+        self.subscription.add(subscription.clone());
+      }
+    }
     subscription
   }
 }
 
 impl<'a, Item, Err> LocalSubject<'a, Item, Err> {
-  pub fn subscribed_size(&self) -> usize { self.observers.borrow_mut().len() }
+  pub fn subscribed_size(&self) -> usize { self.observers.borrow().list.len() }
 }
 #[test]
 fn smoke() {
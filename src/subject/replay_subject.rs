@@ -0,0 +1,403 @@
+use super::{LocalPublishers, SharedPublishers, Terminal};
+use crate::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A Subject that records the items it emits and replays them to every new
+/// subscriber, followed by its terminal notification if it already completed
+/// or errored.
+///
+/// By default all the items are recorded, use
+/// [`max_count`](ReplaySubject::max_count) and
+/// [`max_age`](ReplaySubject::max_age) to bound the buffer.
+#[derive(Clone)]
+pub struct ReplaySubject<O, S, B> {
+  pub(crate) subject: Subject<O, S>,
+  pub(crate) buffer: B,
+}
+
+pub type LocalReplaySubject<'a, Item, Err> = ReplaySubject<
+  LocalPublishers<'a, Item, Err>,
+  LocalSubscription,
+  Rc<RefCell<ReplayBuffer<Item, Err>>>,
+>;
+
+pub type SharedReplaySubject<Item, Err> = ReplaySubject<
+  SharedPublishers<Item, Err>,
+  SharedSubscription,
+  Arc<Mutex<ReplayBuffer<Item, Err>>>,
+>;
+
+subscription_proxy_impl!(ReplaySubject<O, U, B>, {subject}, U, <O, B>);
+
+#[doc(hidden)]
+pub struct ReplayBuffer<Item, Err> {
+  values: VecDeque<(Instant, Item)>,
+  max_count: Option<usize>,
+  max_age: Option<Duration>,
+  // how many items were recorded, trimmed ones included.
+  recorded: usize,
+  terminal: Option<Terminal<Err>>,
+}
+
+impl<Item, Err> Default for ReplayBuffer<Item, Err> {
+  fn default() -> Self {
+    ReplayBuffer {
+      values: VecDeque::new(),
+      max_count: None,
+      max_age: None,
+      recorded: 0,
+      terminal: None,
+    }
+  }
+}
+
+impl<Item, Err> ReplayBuffer<Item, Err> {
+  fn trim(&mut self) {
+    if let Some(max_count) = self.max_count {
+      while self.values.len() > max_count {
+        self.values.pop_front();
+      }
+    }
+    if let Some(max_age) = self.max_age {
+      let now = Instant::now();
+      while let Some((at, _)) = self.values.front() {
+        if now.duration_since(*at) > max_age {
+          self.values.pop_front();
+        } else {
+          break;
+        }
+      }
+    }
+  }
+
+  fn record(&mut self, value: Item) -> bool {
+    if self.terminal.is_some() {
+      return false;
+    }
+    self.values.push_back((Instant::now(), value));
+    self.recorded += 1;
+    self.trim();
+    true
+  }
+
+  fn terminate(&mut self, terminal: Terminal<Err>) -> bool {
+    if self.terminal.is_some() {
+      return false;
+    }
+    self.terminal = Some(terminal);
+    true
+  }
+
+  /// Copies the items recorded after the first `seen` ones out of the
+  /// buffer, so the buffer is not borrowed while they are replayed.
+  fn values_since(&mut self, seen: usize) -> Vec<Item>
+  where
+    Item: PayloadCopy,
+  {
+    self.trim();
+    let count = (self.recorded - seen).min(self.values.len());
+    self
+      .values
+      .iter()
+      .skip(self.values.len() - count)
+      .map(|(_, v)| v.payload_copy())
+      .collect()
+  }
+}
+
+/// Replays the buffer to the subscriber, then registers it to the subject.
+/// The items recorded while replaying are replayed too, and the subscriber is
+/// registered with the buffer held, so no item is missed or received twice.
+#[doc(hidden)]
+macro subscribe_impl(
+  $subject: ident, $subscriber: ident, $($lock: tt $($parentheses: tt)?).+
+) {{
+  let mut seen = 0;
+  loop {
+    let mut buffer = $subject.buffer.$($lock$($parentheses)?).+;
+    let values = buffer.values_since(seen);
+    if values.is_empty() {
+      let terminal = buffer.terminal.as_ref().map(Terminal::payload_copy);
+      if let Some(terminal) = terminal {
+        drop(buffer);
+        terminal.notify(&mut $subscriber);
+        break $subscriber.subscription;
+      }
+      break $subject.subject.actual_subscribe($subscriber);
+    }
+    seen = buffer.recorded;
+    drop(buffer);
+    values.into_iter().for_each(|v| $subscriber.next(v));
+  }
+}}
+
+impl<'a, Item, Err> LocalReplaySubject<'a, Item, Err> {
+  #[inline]
+  pub fn new() -> Self {
+    ReplaySubject {
+      subject: Subject::new(),
+      buffer: Default::default(),
+    }
+  }
+
+  /// Bounds the buffer to the `count` most recent items.
+  #[inline]
+  pub fn max_count(self, count: usize) -> Self {
+    self.buffer.borrow_mut().max_count = Some(count);
+    self
+  }
+
+  /// Bounds the buffer to the items emitted within the last `age`.
+  #[inline]
+  pub fn max_age(self, age: Duration) -> Self {
+    self.buffer.borrow_mut().max_age = Some(age);
+    self
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<Item, Err> SharedReplaySubject<Item, Err> {
+  #[inline]
+  pub fn new() -> Self {
+    ReplaySubject {
+      subject: Subject::new(),
+      buffer: Default::default(),
+    }
+  }
+
+  /// Bounds the buffer to the `count` most recent items.
+  #[inline]
+  pub fn max_count(self, count: usize) -> Self {
+    self.buffer.lock().unwrap().max_count = Some(count);
+    self
+  }
+
+  /// Bounds the buffer to the items emitted within the last `age`.
+  #[inline]
+  pub fn max_age(self, age: Duration) -> Self {
+    self.buffer.lock().unwrap().max_age = Some(age);
+    self
+  }
+
+  #[inline]
+  pub fn subscribed_size(&self) -> usize { self.subject.subscribed_size() }
+}
+
+impl<'a, Item, Err> Default for LocalReplaySubject<'a, Item, Err> {
+  #[inline]
+  fn default() -> Self { Self::new() }
+}
+
+impl<Item, Err> Default for SharedReplaySubject<Item, Err> {
+  #[inline]
+  fn default() -> Self { Self::new() }
+}
+
+impl<'a, Item, Err> Observable for LocalReplaySubject<'a, Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<Item, Err> Observable for SharedReplaySubject<Item, Err> {
+  type Item = Item;
+  type Err = Err;
+}
+
+impl<'a, Item, Err> Observer<Item, Err> for LocalReplaySubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  // the buffer is released before emitting, a subscriber may emit to this
+  // subject.
+  fn next(&mut self, value: Item) {
+    if self.buffer.borrow_mut().record(value.payload_copy()) {
+      self.subject.next(value);
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    let terminal = Terminal::error(err.payload_copy());
+    if self.buffer.borrow_mut().terminate(terminal) {
+      self.subject.error(err);
+    }
+  }
+
+  fn complete(&mut self) {
+    if self.buffer.borrow_mut().terminate(Terminal::Complete) {
+      self.subject.complete();
+    }
+  }
+}
+
+impl<Item, Err> Observer<Item, Err> for SharedReplaySubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  // the buffer is held while emitting, so a subscriber registering from
+  // another thread either finds the item in the buffer or receives it.
+  fn next(&mut self, value: Item) {
+    let mut buffer = self.buffer.lock().unwrap();
+    if buffer.record(value.payload_copy()) {
+      self.subject.next(value);
+    }
+  }
+
+  fn error(&mut self, err: Err) {
+    let mut buffer = self.buffer.lock().unwrap();
+    if buffer.terminate(Terminal::error(err.payload_copy())) {
+      self.subject.error(err);
+    }
+  }
+
+  fn complete(&mut self) {
+    let mut buffer = self.buffer.lock().unwrap();
+    if buffer.terminate(Terminal::Complete) {
+      self.subject.complete();
+    }
+  }
+}
+
+impl<'a, Item, Err> LocalObservable<'a> for LocalReplaySubject<'a, Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = LocalSubscription;
+  fn actual_subscribe<O: Observer<Self::Item, Self::Err> + 'a>(
+    self,
+    mut subscriber: Subscriber<O, LocalSubscription>,
+  ) -> LocalSubscription {
+    subscribe_impl!(self, subscriber, borrow_mut())
+  }
+}
+
+impl<Item, Err> SharedObservable for SharedReplaySubject<Item, Err>
+where
+  Item: PayloadCopy,
+  Err: PayloadCopy,
+{
+  type Unsub = SharedSubscription;
+  fn actual_subscribe<
+    O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
+  >(
+    self,
+    mut subscriber: Subscriber<O, SharedSubscription>,
+  ) -> Self::Unsub {
+    subscribe_impl!(self, subscriber, lock().unwrap())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::prelude::*;
+  use std::sync::{Arc, Mutex};
+  use std::time::Duration;
+
+  #[test]
+  fn replay_all() {
+    let mut values = vec![];
+    {
+      let mut subject = LocalReplaySubject::new();
+      subject.next(1);
+      subject.next(2);
+      subject.clone().subscribe(|v| values.push(v));
+      subject.next(3);
+      assert_eq!(subject.subscribed_size(), 1);
+    }
+    assert_eq!(values, vec![1, 2, 3]);
+  }
+
+  #[test]
+  fn max_count() {
+    let mut values = vec![];
+    {
+      let mut subject = LocalReplaySubject::new().max_count(2);
+      (0..5).for_each(|v| subject.next(v));
+      subject.clone().subscribe(|v| values.push(v));
+    }
+    assert_eq!(values, vec![3, 4]);
+  }
+
+  #[test]
+  fn max_age() {
+    let mut values = vec![];
+    {
+      let mut subject =
+        LocalReplaySubject::new().max_age(Duration::from_millis(20));
+      subject.next(1);
+      std::thread::sleep(Duration::from_millis(30));
+      subject.next(2);
+      subject.clone().subscribe(|v| values.push(v));
+    }
+    assert_eq!(values, vec![2]);
+  }
+
+  #[test]
+  fn replay_terminal() {
+    let mut values = vec![];
+    let mut completed = false;
+    let mut error = None;
+    {
+      let mut subject = LocalReplaySubject::new();
+      subject.next(1);
+      subject.complete();
+      subject.next(2);
+      subject
+        .clone()
+        .subscribe_complete(|v| values.push(v), || completed = true);
+
+      let mut subject = LocalReplaySubject::new();
+      subject.error("");
+      subject
+        .clone()
+        .subscribe_err(|_: i32| {}, |e| error = Some(e));
+      assert_eq!(subject.subscribed_size(), 0);
+    }
+    assert_eq!(values, vec![1]);
+    assert!(completed);
+    assert_eq!(error, Some(""));
+  }
+
+  #[test]
+  fn emit_while_replaying() {
+    let values = std::rc::Rc::new(std::cell::RefCell::new(vec![]));
+    let c_values = values.clone();
+    let mut subject = LocalReplaySubject::new();
+    let mut c_subject = subject.clone();
+    subject.next(1);
+    subject.next(2);
+    subject.clone().subscribe(move |v| {
+      c_values.borrow_mut().push(v);
+      if v == 1 {
+        c_subject.next(3);
+      }
+    });
+    subject.next(4);
+
+    assert_eq!(*values.borrow(), vec![1, 2, 3, 4]);
+  }
+
+  #[test]
+  fn shared() {
+    let values = Arc::new(Mutex::new(vec![]));
+    let c_values = values.clone();
+    let mut subject = SharedReplaySubject::new().max_count(1);
+    subject.next(1);
+    subject.next(2);
+    subject
+      .clone()
+      .to_shared()
+      .subscribe(move |v: i32| c_values.lock().unwrap().push(v));
+    subject.next(3);
+
+    assert_eq!(*values.lock().unwrap(), vec![2, 3]);
+  }
+}
@@ -1,8 +1,9 @@
+use super::{Publishers, Terminal};
 use crate::prelude::*;
 use std::sync::{Arc, Mutex};
 
-type SharedPublishers<Item, Err> =
-  Arc<Mutex<Vec<Box<dyn Publisher<Item, Err> + Send + Sync>>>>;
+pub(crate) type SharedPublishers<Item, Err> =
+  Arc<Mutex<Publishers<Box<dyn Publisher<Item, Err> + Send + Sync>, Err>>>;
 
 pub type SharedSubject<Item, Err> =
   Subject<SharedPublishers<Item, Err>, SharedSubscription>;
@@ -18,18 +19,28 @@ impl<Item, Err> SharedObservable for SharedSubject<Item, Err> {
     O: Observer<Self::Item, Self::Err> + Sync + Send + 'static,
   >(
     mut self,
-    subscriber: Subscriber<O, SharedSubscription>,
+    mut subscriber: Subscriber<O, SharedSubscription>,
   ) -> Self::Unsub {
     let subscription = subscriber.subscription.clone();
-    self.subscription.add(subscription.clone());
-    self.observers.lock().unwrap().push(Box::new(subscriber));
+    let mut observers = self.observers.lock().unwrap();
+    match observers.terminal.as_ref().map(Terminal::payload_copy) {
+      Some(terminal) => {
+        drop(observers);
+        terminal.notify(&mut subscriber);
+      }
+      None => {
+        observers.list.push(Box::new(subscriber));
+        drop(observers);
+        self.subscription.add(subscription.clone());
+      }
+    }
     subscription
   }
 }
 
 impl<Item, Err> SharedSubject<Item, Err> {
   pub fn subscribed_size(&self) -> usize {
-    self.observers.lock().unwrap().len()
+    self.observers.lock().unwrap().list.len()
   }
 }
 #[test]